@@ -0,0 +1,66 @@
+use anyhow::{anyhow, Result};
+
+/// Bounds-checked little-endian accessors over a byte slice. Every `c_*`
+/// method returns a clean `anyhow` error on an out-of-range read instead of
+/// panicking, so feeding the tool a truncated or corrupt GRP/MDL file
+/// produces a message instead of a crash. The `o_*` variants are the same
+/// reads without the error wrapping, for callers that treat a short read as
+/// "field not present" rather than a hard failure.
+pub trait BinReader {
+    fn o_u8(&self, off: usize) -> Option<u8>;
+    fn o_u16(&self, off: usize) -> Option<u16>;
+    fn o_u32(&self, off: usize) -> Option<u32>;
+    fn o_i16(&self, off: usize) -> Option<i16>;
+    fn o_f32(&self, off: usize) -> Option<f32>;
+
+    fn c_u8(&self, off: usize) -> Result<u8>;
+    fn c_u16(&self, off: usize) -> Result<u16>;
+    fn c_u32(&self, off: usize) -> Result<u32>;
+    fn c_i16(&self, off: usize) -> Result<i16>;
+    fn c_f32(&self, off: usize) -> Result<f32>;
+    fn c_cstr(&self, off: usize) -> Result<String>;
+}
+
+impl BinReader for [u8] {
+    fn o_u8(&self, off: usize) -> Option<u8> {
+        self.get(off).copied()
+    }
+    fn o_u16(&self, off: usize) -> Option<u16> {
+        self.get(off..off + 2).map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+    }
+    fn o_u32(&self, off: usize) -> Option<u32> {
+        self.get(off..off + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+    }
+    fn o_i16(&self, off: usize) -> Option<i16> {
+        self.get(off..off + 2).map(|b| i16::from_le_bytes(b.try_into().unwrap()))
+    }
+    fn o_f32(&self, off: usize) -> Option<f32> {
+        self.get(off..off + 4).map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+    }
+
+    fn c_u8(&self, off: usize) -> Result<u8> {
+        self.o_u8(off).ok_or_else(|| anyhow!("EOF at 0x{off:X}"))
+    }
+    fn c_u16(&self, off: usize) -> Result<u16> {
+        self.o_u16(off).ok_or_else(|| anyhow!("EOF at 0x{off:X}"))
+    }
+    fn c_u32(&self, off: usize) -> Result<u32> {
+        self.o_u32(off).ok_or_else(|| anyhow!("EOF at 0x{off:X}"))
+    }
+    fn c_i16(&self, off: usize) -> Result<i16> {
+        self.o_i16(off).ok_or_else(|| anyhow!("EOF at 0x{off:X}"))
+    }
+    fn c_f32(&self, off: usize) -> Result<f32> {
+        self.o_f32(off).ok_or_else(|| anyhow!("EOF at 0x{off:X}"))
+    }
+    fn c_cstr(&self, off: usize) -> Result<String> {
+        let tail = self.get(off..).ok_or_else(|| anyhow!("EOF at 0x{off:X}"))?;
+        let end = tail
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| anyhow!("unterminated string at 0x{off:X}"))?;
+        std::str::from_utf8(&tail[..end])
+            .map(|s| s.to_string())
+            .map_err(|e| anyhow!("invalid utf-8 string at 0x{off:X}: {e}"))
+    }
+}
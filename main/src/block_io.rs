@@ -0,0 +1,74 @@
+use anyhow::{anyhow, Context, Result};
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// Something that can serve an arbitrary byte range on demand. Implemented
+/// by [`BlockReader`] (seeks into the archive on disk) and by `Vec<u8>`
+/// (slices an already-decompressed nested container's bytes), so entry
+/// payloads don't all have to be resident in memory at once.
+pub trait ByteSource {
+    fn read(&self, start: u64, size: u64) -> Result<Vec<u8>>;
+    /// Total length of the underlying data, used to size a trailing entry
+    /// whose end isn't given explicitly by the index table.
+    fn len(&self) -> u64;
+}
+
+/// Seeks into a GRP2 file on disk and serves entry payloads on demand,
+/// instead of `fs::read`-ing the whole (possibly multi-gigabyte) archive
+/// up front. The header/name/index tables are still small enough to read
+/// eagerly; only entry payloads are streamed lazily through this.
+pub struct BlockReader {
+    path: PathBuf,
+    file: RefCell<File>,
+    len: u64,
+}
+
+impl BlockReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+        let len = file.metadata()?.len();
+        Ok(Self { path: path.to_path_buf(), file: RefCell::new(file), len })
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn read_range(&self, start: u64, size: u64) -> Result<Vec<u8>> {
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start(start))
+            .with_context(|| format!("seeking to 0x{start:X} in {}", self.path.display()))?;
+        let mut buf = vec![0u8; size as usize];
+        file.read_exact(&mut buf)
+            .with_context(|| format!("reading 0x{size:X} bytes at 0x{start:X} from {}", self.path.display()))?;
+        Ok(buf)
+    }
+}
+
+impl ByteSource for BlockReader {
+    fn read(&self, start: u64, size: u64) -> Result<Vec<u8>> {
+        self.read_range(start, size)
+    }
+
+    fn len(&self) -> u64 {
+        self.len
+    }
+}
+
+impl ByteSource for Vec<u8> {
+    fn read(&self, start: u64, size: u64) -> Result<Vec<u8>> {
+        let s = start as usize;
+        let e = s.checked_add(size as usize).ok_or_else(|| anyhow!("range overflow"))?;
+        self.get(s..e)
+            .map(|b| b.to_vec())
+            .ok_or_else(|| anyhow!("EOF reading 0x{size:X} bytes at 0x{s:X}"))
+    }
+
+    fn len(&self) -> u64 {
+        Vec::len(self) as u64
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::{anyhow, Result};
+use std::io::{Cursor, Read};
+
+/// A pluggable entry-payload codec: recognizes its own magic bytes and
+/// decodes a compressed blob back to raw bytes. `GrpFile::parse` probes
+/// every registered codec against an entry's leading bytes instead of
+/// hardcoding a single zstd magic comparison, so new container compressions
+/// can be added here without touching the parse loop.
+pub trait Codec {
+    fn name(&self) -> &'static str;
+    fn detect(&self, magic: &[u8]) -> bool;
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<u8>>;
+    /// Compresses raw bytes back into this codec's format. Only `ZstdCodec`
+    /// implements this today (GRP2 payloads are always repacked as zstd);
+    /// every other codec is decode-only until a real write path needs them.
+    fn encode(&self, _bytes: &[u8]) -> Result<Vec<u8>> {
+        Err(anyhow!("{} does not support re-encoding", self.name()))
+    }
+
+    /// Decodes only enough of `bytes` to produce up to `max_len` bytes of
+    /// output, for callers (entry classification) that only need to sniff
+    /// the front of a payload. The default just truncates a full decode;
+    /// `ZstdCodec` overrides this to actually stop decompressing early
+    /// instead of paying for the whole entry just to classify it.
+    fn decode_prefix(&self, bytes: &[u8], max_len: usize) -> Result<Vec<u8>> {
+        let mut out = self.decode(bytes)?;
+        out.truncate(max_len);
+        Ok(out)
+    }
+}
+
+pub struct RawCodec;
+impl Codec for RawCodec {
+    fn name(&self) -> &'static str {
+        "Raw"
+    }
+    fn detect(&self, _magic: &[u8]) -> bool {
+        false // fallback only; never wins auto-detection
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        Ok(bytes.to_vec())
+    }
+}
+
+pub struct ZstdCodec;
+impl Codec for ZstdCodec {
+    fn name(&self) -> &'static str {
+        "Zstd"
+    }
+    fn detect(&self, magic: &[u8]) -> bool {
+        magic.len() >= 4 && magic[0..4] == [0x28, 0xB5, 0x2F, 0xFD]
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut dec = zstd::stream::read::Decoder::new(Cursor::new(bytes))?;
+        let mut out = Vec::with_capacity(bytes.len() * 2);
+        dec.read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    fn encode(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        Ok(zstd::stream::encode_all(Cursor::new(bytes), 19)?)
+    }
+
+    fn decode_prefix(&self, bytes: &[u8], max_len: usize) -> Result<Vec<u8>> {
+        let dec = zstd::stream::read::Decoder::new(Cursor::new(bytes))?;
+        let mut out = Vec::with_capacity(max_len);
+        dec.take(max_len as u64).read_to_end(&mut out)?;
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "lzma")]
+pub struct LzmaCodec;
+#[cfg(feature = "lzma")]
+impl Codec for LzmaCodec {
+    fn name(&self) -> &'static str {
+        "Lzma"
+    }
+    fn detect(&self, magic: &[u8]) -> bool {
+        // XZ container magic; raw LZMA streams have no reliable magic of
+        // their own so we only auto-detect the XZ-wrapped form.
+        magic.len() >= 6 && magic[0..6] == [0xFD, b'7', b'z', b'X', b'Z', 0x00]
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        liblzma::read::XzDecoder::new(bytes).read_to_end(&mut out)?;
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "bzip2")]
+pub struct Bzip2Codec;
+#[cfg(feature = "bzip2")]
+impl Codec for Bzip2Codec {
+    fn name(&self) -> &'static str {
+        "Bzip2"
+    }
+    fn detect(&self, magic: &[u8]) -> bool {
+        magic.len() >= 3 && &magic[0..3] == b"BZh"
+    }
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        bzip2::read::BzDecoder::new(bytes).read_to_end(&mut out)?;
+        Ok(out)
+    }
+}
+
+/// All codecs this build was compiled with, in detection priority order.
+/// `RawCodec` is last and never auto-detected; it's the fallback for magic
+/// nothing else recognizes.
+pub fn registry() -> Vec<Box<dyn Codec>> {
+    let mut v: Vec<Box<dyn Codec>> = vec![Box::new(ZstdCodec)];
+    #[cfg(feature = "lzma")]
+    v.push(Box::new(LzmaCodec));
+    #[cfg(feature = "bzip2")]
+    v.push(Box::new(Bzip2Codec));
+    v.push(Box::new(RawCodec));
+    v
+}
+
+/// Detects which codec's magic matches `magic`, returning its name. Falls
+/// back to `"Raw"` when nothing recognizes it.
+pub fn detect_name(magic: &[u8]) -> &'static str {
+    for codec in registry() {
+        if codec.detect(magic) {
+            return codec.name();
+        }
+    }
+    "Raw"
+}
+
+/// Decodes `bytes` with the named codec.
+pub fn decode_with(name: &str, bytes: &[u8]) -> Result<Vec<u8>> {
+    registry()
+        .into_iter()
+        .find(|c| c.name() == name)
+        .ok_or_else(|| anyhow!("unknown codec {name:?}"))?
+        .decode(bytes)
+}
+
+/// Decodes only enough of `bytes` (with the named codec) to produce up to
+/// `max_len` bytes — see [`Codec::decode_prefix`].
+pub fn decode_prefix(name: &str, bytes: &[u8], max_len: usize) -> Result<Vec<u8>> {
+    registry()
+        .into_iter()
+        .find(|c| c.name() == name)
+        .ok_or_else(|| anyhow!("unknown codec {name:?}"))?
+        .decode_prefix(bytes, max_len)
+}
+
+/// Encodes `bytes` with the named codec, for repacking entry payloads
+/// (`writer::write_grp` always passes `"Zstd"`).
+pub fn encode_with(name: &str, bytes: &[u8]) -> Result<Vec<u8>> {
+    registry()
+        .into_iter()
+        .find(|c| c.name() == name)
+        .ok_or_else(|| anyhow!("unknown codec {name:?}"))?
+        .encode(bytes)
+}
@@ -0,0 +1,76 @@
+use crate::mdl;
+
+/// Coarse classification of a decompressed entry's content, used to drive
+/// tree icons and to route viewers without trusting the (often wrong or
+/// missing) file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Grp2,
+    ZstdStream,
+    Model,
+    Texture,
+    Text,
+    Unknown,
+}
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+const DDS_MAGIC: [u8; 4] = *b"DDS ";
+const PNG_MAGIC: [u8; 8] = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+impl FileKind {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            FileKind::Grp2 => "\u{1F4E6}",
+            FileKind::ZstdStream => "\u{1F5DC}",
+            FileKind::Model => "\u{1F9CA}",
+            FileKind::Texture => "\u{1F5BC}",
+            FileKind::Text => "\u{1F4C4}",
+            FileKind::Unknown => "\u{2754}",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileKind::Grp2 => "GRP2 archive",
+            FileKind::ZstdStream => "zstd stream",
+            FileKind::Model => "model (MDL)",
+            FileKind::Texture => "texture",
+            FileKind::Text => "text",
+            FileKind::Unknown => "unknown",
+        }
+    }
+}
+
+/// Classifies decompressed entry bytes by leading magic and a few structural
+/// probes. Cheap enough to run once per entry at tree-build time.
+pub fn detect_kind(bytes: &[u8]) -> FileKind {
+    if bytes.len() >= 4 && &bytes[0..4] == b"GRP2" {
+        return FileKind::Grp2;
+    }
+    if bytes.len() >= 4 && bytes[0..4] == ZSTD_MAGIC {
+        return FileKind::ZstdStream;
+    }
+    if mdl::looks_like_mdl(bytes) {
+        return FileKind::Model;
+    }
+    if bytes.len() >= 4 && bytes[0..4] == DDS_MAGIC {
+        return FileKind::Texture;
+    }
+    if bytes.len() >= 8 && bytes[0..8] == PNG_MAGIC {
+        return FileKind::Texture;
+    }
+    if looks_like_text(bytes) {
+        return FileKind::Text;
+    }
+    FileKind::Unknown
+}
+
+fn looks_like_text(bytes: &[u8]) -> bool {
+    if bytes.is_empty() {
+        return false;
+    }
+    let sample = &bytes[..bytes.len().min(512)];
+    std::str::from_utf8(sample).is_ok_and(|s| {
+        s.chars().all(|c| c == '\n' || c == '\r' || c == '\t' || !c.is_control())
+    })
+}
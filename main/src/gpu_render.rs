@@ -0,0 +1,287 @@
+//! Offscreen wgpu rendering backend for [`crate::mdl_viewer::ModelViewer`],
+//! compiled in only behind the `wgpu-renderer` feature. Mirrors the
+//! opengl/wgpu backend split helix's fast3d crate uses for its model
+//! preview: the CPU wireframe rasterizer in `mdl_viewer.rs` stays the
+//! always-available default, this module takes over only once the feature
+//! is enabled *and* the frame is wgpu-backed ([`GpuRenderer::new`] builds
+//! on the device eframe already created), so headless/CI builds keep
+//! working unchanged.
+#![cfg(feature = "wgpu-renderer")]
+
+use eframe::egui_wgpu::{self, wgpu};
+use wgpu::util::DeviceExt;
+
+use crate::mdl::MdlChunk;
+
+const COLOR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Vertex/index buffers for one uploaded group/LOD. Rebuilt only when the
+/// selection changes, not every frame.
+struct GpuMesh {
+    vertex_buf: wgpu::Buffer,
+    index_buf: wgpu::Buffer,
+    index_count: u32,
+}
+
+/// A shaded offscreen render target plus the pipeline that draws into it,
+/// presented to egui as a plain [`egui::TextureId`] each frame.
+pub struct GpuRenderer {
+    pipeline: wgpu::RenderPipeline,
+    uniform_buf: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    color_view: wgpu::TextureView,
+    depth_view: wgpu::TextureView,
+    texture_id: egui::TextureId,
+    /// (group, lod) the currently uploaded mesh was built from, so we only
+    /// re-upload when the user picks a different model or LOD.
+    mesh: Option<((usize, usize), GpuMesh)>,
+}
+
+impl GpuRenderer {
+    /// Builds the pipeline, a fixed-size `size` offscreen color+depth
+    /// target, and registers the color target with egui's wgpu renderer.
+    /// Always returns `Some`: a wgpu error while creating these resources
+    /// goes to the device's error handler, not to this return value.
+    pub fn new(render_state: &egui_wgpu::RenderState, size: [u32; 2]) -> Option<Self> {
+        let device = &render_state.device;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("mdl_viewer_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("mdl_viewer.wgsl").into()),
+        });
+
+        let uniform_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("mdl_viewer_uniforms"),
+            size: std::mem::size_of::<[f32; 16]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("mdl_viewer_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("mdl_viewer_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buf.as_entire_binding(),
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("mdl_viewer_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("mdl_viewer_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<[f32; 3]>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &[wgpu::VertexAttribute {
+                        offset: 0,
+                        shader_location: 0,
+                        format: wgpu::VertexFormat::Float32x3,
+                    }],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: COLOR_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None, // MDL winding order isn't established yet; don't cull backfaces
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let color_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("mdl_viewer_color"),
+            size: wgpu::Extent3d { width: size[0], height: size[1], depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: COLOR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let color_view = color_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("mdl_viewer_depth"),
+            size: wgpu::Extent3d { width: size[0], height: size[1], depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let texture_id = render_state.renderer.write().register_native_texture(
+            device,
+            &color_view,
+            wgpu::FilterMode::Linear,
+        );
+
+        Some(Self { pipeline, uniform_buf, bind_group, color_view, depth_view, texture_id, mesh: None })
+    }
+
+    /// Uploads `chunk` if `(group, lod)` differs from what's currently on
+    /// the GPU, draws it lit with `mvp`, and returns the egui texture the
+    /// result landed in.
+    pub fn render(
+        &mut self,
+        render_state: &egui_wgpu::RenderState,
+        group: usize,
+        lod: usize,
+        chunk: &MdlChunk,
+        mvp: [[f32; 4]; 4],
+    ) -> egui::TextureId {
+        let device = &render_state.device;
+        let queue = &render_state.queue;
+
+        if self.mesh.as_ref().map(|(key, _)| *key) != Some((group, lod)) {
+            self.mesh = Some(((group, lod), Self::upload_mesh(device, chunk)));
+        }
+        let mesh = &self.mesh.as_ref().unwrap().1;
+
+        queue.write_buffer(&self.uniform_buf, 0, bytemuck_mat4(&mvp));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("mdl_viewer_encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("mdl_viewer_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.06, g: 0.06, b: 0.08, a: 1.0 }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Store }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.set_vertex_buffer(0, mesh.vertex_buf.slice(..));
+            pass.set_index_buffer(mesh.index_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+        }
+        queue.submit(Some(encoder.finish()));
+
+        self.texture_id
+    }
+
+    fn upload_mesh(device: &wgpu::Device, chunk: &MdlChunk) -> GpuMesh {
+        let vertex_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("mdl_viewer_vertices"),
+            contents: bytemuck_vertices(&chunk.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("mdl_viewer_indices"),
+            contents: bytemuck_indices(&chunk.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        GpuMesh { vertex_buf, index_buf, index_count: chunk.indices.len() as u32 * 3 }
+    }
+}
+
+/// `MdlChunk`'s vertex/index types aren't `bytemuck::Pod` (no dependency on
+/// bytemuck elsewhere in this crate), so these reinterpret the already
+/// tightly-packed `[f32; 3]`/`[u32; 3]` slices as raw bytes by hand instead
+/// of pulling in the crate for three call sites.
+fn bytemuck_vertices(v: &[[f32; 3]]) -> &[u8] {
+    let ptr = v.as_ptr() as *const u8;
+    unsafe { std::slice::from_raw_parts(ptr, std::mem::size_of_val(v)) }
+}
+
+fn bytemuck_indices(v: &[[u32; 3]]) -> &[u8] {
+    let ptr = v.as_ptr() as *const u8;
+    unsafe { std::slice::from_raw_parts(ptr, std::mem::size_of_val(v)) }
+}
+
+fn bytemuck_mat4(m: &[[f32; 4]; 4]) -> &[u8] {
+    let ptr = m.as_ptr() as *const u8;
+    unsafe { std::slice::from_raw_parts(ptr, std::mem::size_of_val(m)) }
+}
+
+/// Tiny column-major 4x4 matrix helpers for the orbit camera, so this
+/// feature doesn't have to pull in a whole math crate for four matrices.
+/// Each `[[f32; 4]; 4]` is an array of columns, matching WGSL's `mat4x4`
+/// uniform layout.
+pub fn mat4_mul(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut out = [[0.0f32; 4]; 4];
+    for col in 0..4 {
+        for row in 0..4 {
+            out[col][row] = (0..4).map(|k| a[k][row] * b[col][k]).sum();
+        }
+    }
+    out
+}
+
+pub fn mat4_perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> [[f32; 4]; 4] {
+    let f = 1.0 / (fov_y * 0.5).tan();
+    let range_inv = 1.0 / (near - far);
+    [
+        [f / aspect, 0.0, 0.0, 0.0],
+        [0.0, f, 0.0, 0.0],
+        [0.0, 0.0, (far + near) * range_inv, -1.0],
+        [0.0, 0.0, near * far * range_inv * 2.0, 0.0],
+    ]
+}
+
+pub fn mat4_translate(x: f32, y: f32, z: f32) -> [[f32; 4]; 4] {
+    [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [x, y, z, 1.0]]
+}
+
+pub fn mat4_rotate_x(angle: f32) -> [[f32; 4]; 4] {
+    let (s, c) = angle.sin_cos();
+    [[1.0, 0.0, 0.0, 0.0], [0.0, c, s, 0.0], [0.0, -s, c, 0.0], [0.0, 0.0, 0.0, 1.0]]
+}
+
+pub fn mat4_rotate_y(angle: f32) -> [[f32; 4]; 4] {
+    let (s, c) = angle.sin_cos();
+    [[c, 0.0, -s, 0.0], [0.0, 1.0, 0.0, 0.0], [s, 0.0, c, 0.0], [0.0, 0.0, 0.0, 1.0]]
+}
@@ -1,55 +1,246 @@
 use core::num;
-use std::{collections::{btree_map::Entry, BTreeMap}, fs, io::{Cursor, Read}, path::{Path, PathBuf}};
-use anyhow::{Context, Result};
+use std::{collections::{btree_map::Entry, BTreeMap}, fs, path::{Path, PathBuf}, rc::Rc};
+use anyhow::{anyhow, Context, Result};
 use eframe::egui::{self, Button};
 use egui::debug_text::print;
 use rfd::FileDialog;
 mod mdl;
 mod mdl_viewer;
+mod filekind;
+mod binreader;
+mod manifest;
+mod codec;
+mod block_io;
+mod writer;
+#[cfg(feature = "wgpu-renderer")]
+mod gpu_render;
+mod tfd;
+mod tex_viewer;
 use mdl_viewer::ModelViewer;
+use tex_viewer::TextureViewer;
+use filekind::{detect_kind, FileKind};
+use binreader::BinReader;
+use block_io::{BlockReader, ByteSource};
 use egui::Align2;
 
-const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
-
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 struct GrpEntry {
     index: u32,
     full_path: String,
     start:u64,
     size: u64,
-    compression: Compression,
+    /// Name of the codec (from the [`codec`] registry) this entry's bytes
+    /// were detected as, e.g. `"Zstd"` or `"Raw"`.
+    compression: &'static str,
+    /// Where to fetch this entry's still-compressed bytes from on demand:
+    /// the root archive on disk, or an already-decompressed nested
+    /// container's bytes held in memory.
+    source: Rc<dyn ByteSource>,
+    kind: FileKind,
+    /// The 4-byte file ID and 4-byte common ID that sit alongside this
+    /// entry's location in the data index table. Their meaning hasn't been
+    /// decoded, so they're carried through untouched and only ever read
+    /// back out by [`writer`] when repacking.
+    file_id: u32,
+    common_id: u32,
+    /// True if this entry was spliced in from a nested GRP2 container by
+    /// [`expand_nested_archives`], rather than being one of the root
+    /// archive's own entries. Set explicitly at splice time rather than
+    /// inferred from `full_path` text, since a root entry can legitimately
+    /// share a name with another entry's path prefix (e.g. a root file
+    /// literally named `models` alongside `models/car.mdl`).
+    is_nested: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Compression { Raw, Zstd } //I haven't found a single file that doesn't start with zstd magic yet
+impl std::fmt::Debug for GrpEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrpEntry")
+            .field("index", &self.index)
+            .field("full_path", &self.full_path)
+            .field("start", &self.start)
+            .field("size", &self.size)
+            .field("compression", &self.compression)
+            .field("kind", &self.kind)
+            .finish()
+    }
+}
 
-#[derive(Debug)]
 struct GrpFile {
     path: PathBuf,
-    file_data: Vec<u8>,
+    io: Rc<BlockReader>,
     header_size: u32,
+    /// Bytes 0x08..0x14 of the root header: 12 bytes between `header_size`
+    /// and `file_count` whose meaning isn't decoded yet. Carried through
+    /// verbatim so [`writer::write_grp`] can round-trip them unchanged.
+    header_prelude: [u8; 12],
     file_count: u32,
     data_start: u32,
     entries: Vec<GrpEntry>,
+    /// Full path -> replacement raw bytes, staged by "Replace bytes from
+    /// file…" and applied by [`GrpFile::read_entry`] ahead of whatever is
+    /// still on disk. Keyed by path rather than `GrpEntry::index`, which is
+    /// only unique within a single container's own table. Only consulted
+    /// (and written back) for root-level entries; see [`writer::write_grp`].
+    overrides: BTreeMap<String, Vec<u8>>,
 }
 
 impl GrpFile {
     fn parse(grp_path: &Path) -> Result<Self> {
-        let file_data = fs::read(grp_path).with_context(|| "select the grp file")?;
+        let io = Rc::new(BlockReader::open(grp_path)?);
+        let (header_size, header_prelude, file_count, mut entries, data_start) = parse_header_region(&io)?;
+        expand_nested_archives(&mut entries)?;
+        reclassify_tfd_pairs(&mut entries);
+        Ok(GrpFile {
+            path: grp_path.to_path_buf(),
+            io,
+            header_size,
+            header_prelude,
+            file_count,
+            data_start,
+            entries,
+            overrides: BTreeMap::new(),
+        })
+    }
+
+    /// Stages `raw` as the replacement content for the entry at `full_path`,
+    /// to be re-compressed and written out the next time [`GrpFile::write`]
+    /// runs.
+    fn set_override(&mut self, full_path: &str, raw: Vec<u8>) {
+        self.overrides.insert(full_path.to_string(), raw);
+    }
+
+    /// Reconstructs the GRP2 layout for this pack's root-level entries —
+    /// header, name table, data index and re-zstd-compressed payloads — and
+    /// writes it to `out`. See [`writer::write_grp`] for the format and its
+    /// known limitations.
+    fn write(&self, out: &Path) -> Result<()> {
+        writer::write_grp(self, out)
+    }
+
+    //Extracts the entry
+    fn extract_entry(&self, entry: &GrpEntry, out_dir: &Path) -> Result<PathBuf> {
+        // Nested GRP2 containers aren't written as an opaque blob: that would
+        // clash with the subfolder their own entries live under. Recurse into
+        // the spliced-in children instead.
+        if self.has_nested_children(entry) {
+            let prefix = format!("{}/", entry.full_path);
+            for child in self.entries.iter().filter(|e| e.full_path.starts_with(&prefix)) {
+                self.extract_entry(child, out_dir)?;
+            }
+            return Ok(out_dir.join(&entry.full_path));
+        }
+        let out = self.read_entry(entry)?;
+        let out_path = out_dir.join(&entry.full_path);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&out_path, out)?;
+        Ok(out_path)
+    }
+
+    /// Returns an entry's decompressed bytes — its staged override if one
+    /// was set via [`GrpFile::set_override`], otherwise its bytes as they
+    /// actually sit in the archive.
+    fn read_entry(&self, entry: &GrpEntry) -> Result<Vec<u8>> {
+        if let Some(raw) = self.overrides.get(&entry.full_path) {
+            return Ok(raw.clone());
+        }
+        decompress(&self.raw_bytes(entry)?, entry.compression)
+    }
+
+    /// Fetches an entry's still-compressed bytes on demand from wherever
+    /// they live (the archive on disk, or a parent container's
+    /// decompressed payload), instead of holding the whole archive resident.
+    fn raw_bytes(&self, entry: &GrpEntry) -> Result<Vec<u8>> {
+        entry.source.read(entry.start, entry.size)
+    }
+
+    /// True if `entry` is a nested GRP2 container that was expanded into
+    /// virtual children by `expand_nested_archives`. Gated on `is_nested`
+    /// rather than path text alone, for the same reason as
+    /// [`GrpFile::is_nested_child`]: a root entry can legitimately share a
+    /// name with another entry's path prefix (e.g. a root file literally
+    /// named `models` alongside `models/car.mdl`) without being that
+    /// entry's container.
+    fn has_nested_children(&self, entry: &GrpEntry) -> bool {
+        let prefix = format!("{}/", entry.full_path);
+        self.entries.iter().any(|e| e.is_nested && e.full_path.starts_with(&prefix))
+    }
+
+    /// True if `entry` is itself a virtual child spliced in from a nested
+    /// GRP2 container. Used to keep editing/repacking scoped to root-level
+    /// entries, since [`writer::write_grp`] doesn't rebuild nested
+    /// containers. Backed by the `is_nested` flag `expand_nested_archives`
+    /// sets at splice time, not by reconstructing it from path text — a
+    /// root entry can legitimately share a name with another entry's path
+    /// prefix without being that entry's nested child.
+    fn is_nested_child(&self, entry: &GrpEntry) -> bool {
+        entry.is_nested
+    }
+}
+
+/// Eagerly reads just the header/name/index-table region of the root
+/// archive up front (a few KB to a few hundred KB, never the multi-gigabyte
+/// payload section), growing the read window and retrying if the tables
+/// turn out to extend past what was read. Entry payloads are left on disk
+/// and fetched lazily through `io` as needed.
+fn parse_header_region(io: &Rc<BlockReader>) -> Result<(u32, [u8; 12], u32, Vec<GrpEntry>, u32)> {
+    let mut window = 64 * 1024u64;
+    loop {
+        let capped = window.min(io.len());
+        let header = io.read_range(0, capped)?;
+        match parse_entry_table(&header, io.clone() as Rc<dyn ByteSource>) {
+            Ok((header_size, file_count, entries, data_start)) => {
+                let mut header_prelude = [0u8; 12];
+                header_prelude.copy_from_slice(&header[0x08..0x14]);
+                return Ok((header_size, header_prelude, file_count, entries, data_start));
+            }
+            Err(_) if capped < io.len() => {
+                window = (window * 2).min(io.len());
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Walks a GRP2's header/name/index tables and returns its flat entry list,
+/// with every entry's `source` set to `source`. Shared between the
+/// top-level archive (backed by the [`BlockReader`]) and nested GRP2
+/// containers spliced in by [`expand_nested_archives`] (backed by the
+/// container's own decompressed bytes).
+fn parse_entry_table(file_data: &[u8], source: Rc<dyn ByteSource>) -> Result<(u32, u32, Vec<GrpEntry>, u32)> {
         // Check if true grp
-        if &file_data[0..4] != b"GRP2" {
+        if file_data.get(0..4) != Some(&b"GRP2"[..]) {
             anyhow::bail!("Not a GRP2 file");
         }
-        let header_size = get_u32(&file_data, 0x04)?;
-        let file_count  = get_u32(&file_data, 0x14)?;
+        let header_size = file_data.c_u32(0x04)?;
+        let file_count  = file_data.c_u32(0x14)?;
         println!("files: {file_count}");
+        // An empty table has no name/index entries to walk, and the
+        // `.first()`/`.last()`/`file_count - 1` arithmetic below all assume
+        // at least one file — handle it up front instead of panicking.
+        if file_count == 0 {
+            return Ok((header_size, file_count, Vec::new(), header_size));
+        }
+        // The name-offset table is 4 bytes/entry starting at 0x40; bounds-check
+        // `file_count` against what's actually in `file_data` before trusting it
+        // into a `Vec::with_capacity` below. A corrupt (or truncated-window)
+        // `file_count` would otherwise drive an allocation large enough to abort
+        // the process outright, before any bounds-checked `c_u32` read gets the
+        // chance to fail cleanly instead.
+        let name_table_end = 0x40u64 + (file_count as u64).saturating_mul(4);
+        if name_table_end > file_data.len() as u64 {
+            anyhow::bail!(
+                "file_count {file_count} claims a name table past the end of the header region we have"
+            );
+        }
         // Finds the begining of the file path/name and pushes it into the file name offset vector
         let mut file_name_offsets = Vec::with_capacity(file_count as usize);
         let mut off = 0x40;
         let mut debug_counter = 0;
         let mut debug_offset = 0;
         for _ in 0..file_count {
-            debug_offset = get_u32(&file_data, off)?;
+            debug_offset = file_data.c_u32(off)?;
             file_name_offsets.push(debug_offset);
             println!("filename offset {debug_offset:08X} {debug_counter}");
             off += 4;
@@ -58,20 +249,29 @@ impl GrpFile {
         // Finds the path/name of the file and pushes it into the file name vector
         let mut file_names = Vec::with_capacity(file_count as usize);
         for &name_offset in &file_name_offsets {
-            let name_string = read_cstr(&file_data, name_offset as usize)
+            let name_string = file_data.c_cstr(name_offset as usize)
                 .with_context(|| format!("reading name at 0x{name_offset:08X}"))?;
             file_names.push(name_string);
         }
         // Crude way to locate the data index table but I haven't found a pointer to it yet
         let data_index_start = (*file_name_offsets.last().unwrap() as usize) + file_names.last().unwrap().len() + 5;
         println!("data index start: {data_index_start:08X}");
-        // Locates the begining of each file
+        // Locates the begining of each file, plus its file ID and common ID
+        // (meaning not decoded, but kept around so `writer` can round-trip
+        // them unchanged when repacking)
         let mut file_entry_data_begining: Vec<u32> = Vec::with_capacity(file_count as usize - 1);
+        let mut file_ids: Vec<u32> = Vec::with_capacity(file_count as usize);
+        let mut common_ids: Vec<u32> = Vec::with_capacity(file_count as usize);
         let mut _tmp_offset = data_index_start;
         for _ in 0..(file_count) {
             println!("{_tmp_offset:08X}");
-            let file_loc = get_u32(&file_data, _tmp_offset)?; _tmp_offset += 12; //skips over the 4byte File location, 4byte file ID and the 4byte Common id
-            file_entry_data_begining.push(file_loc);       
+            let file_loc = file_data.c_u32(_tmp_offset)?;
+            let file_id = file_data.c_u32(_tmp_offset + 4)?;
+            let common_id = file_data.c_u32(_tmp_offset + 8)?;
+            _tmp_offset += 12;
+            file_entry_data_begining.push(file_loc);
+            file_ids.push(file_id);
+            common_ids.push(common_id);
             println!("file at {file_loc:08X}");
         }
         //Create an entry vector
@@ -86,92 +286,155 @@ impl GrpFile {
             let size = ((file_entry_data_begining[i + 1] - file_entry_data_begining[i]) as u64);
             let start = (file_entry_data_begining[i] as u64);
             let full_path = file_names[i].clone();
-            let magic = &file_data[start as usize..start as usize + 4.min(size as usize)];
-            let compression = if magic == ZSTD_MAGIC { Compression::Zstd } else { Compression::Raw };
+            let magic_len = 8.min(size);
+            let magic = source.read(start, magic_len).unwrap_or_default();
+            let compression = codec::detect_name(&magic);
             entries.push(GrpEntry {
                 index: i as u32,
                 full_path,
                 start,
                 size,
                 compression,
+                source: source.clone(),
+                kind: FileKind::Unknown,
+                file_id: file_ids[i],
+                common_id: common_ids[i],
+                is_nested: false,
             });
             i += 1;
         }
-        let _temp_debug_file_data_len = file_data.len();
+        let _temp_debug_file_data_len = source.len();
         println!("{_temp_debug_file_data_len:08X}");
 
-        let size = (((file_data.len() as u32) - file_entry_data_begining[i]) as u64);
+        let size = ((source.len() as u32) - file_entry_data_begining[i]) as u64;
         let start = (file_entry_data_begining[i] as u64);
         let full_path = file_names[i].clone();
-        let magic = &file_data[start as usize..start as usize +4.min(size as usize)];
-        let compression = if magic == ZSTD_MAGIC {Compression::Zstd} else {Compression::Raw};
+        let magic_len = 8.min(size);
+        let magic = source.read(start, magic_len).unwrap_or_default();
+        let compression = codec::detect_name(&magic);
         entries.push(GrpEntry{
             index: i as u32,
-            full_path, 
-            start, 
-            size, 
-            compression
+            full_path,
+            start,
+            size,
+            compression,
+            source: source.clone(),
+            kind: FileKind::Unknown,
+            file_id: file_ids[i],
+            common_id: common_ids[i],
+            is_nested: false,
         });
 
+    Ok((header_size, file_count, entries, data_start))
+}
 
-        Ok(GrpFile {
-            path: grp_path.to_path_buf(),
-            file_data,
-            header_size,
-            file_count,
-            data_start,
-            entries,
-        })
-    }
-    //Extracts the entry
-    fn extract_entry(&self, entry: &GrpEntry, out_dir: &Path) -> Result<PathBuf> {
-        let bytes = &self.file_data[entry.start as usize .. (entry.start + entry.size) as usize];
-        let out_path = out_dir.join(&entry.full_path);
-        if let Some(parent) = out_path.parent() {
-            fs::create_dir_all(parent)?;
+/// How many decompressed bytes of an entry's payload are sampled to run
+/// `detect_kind` — enough to cover `looks_like_mdl`'s 0x110-byte header
+/// check and `looks_like_text`'s 512-byte sample. Classifying from a short
+/// prefix (rather than the full decode) is what lets a streaming codec
+/// (`Zstd`) avoid decompressing the whole payload just to find out it
+/// isn't a nested archive.
+const CLASSIFY_PROBE_LEN: usize = 1024;
+
+/// Recursively splices nested GRP2 containers into `entries`. For each
+/// entry, decodes a short prefix of its bytes (see `CLASSIFY_PROBE_LEN`) to
+/// classify it; only entries that prefix-sniff as `GRP2` pay for a full
+/// decode, which is then parsed as another archive and pushed in with
+/// `full_path` prefixed by the parent's, so the nested pack shows up as a
+/// virtual subfolder in the tree. Repeats until nothing new is found, so
+/// containers nested inside containers are expanded to arbitrary depth.
+///
+/// A single entry that fails to read or decode (corrupt payload, or magic
+/// bytes that only *look* like a known codec's) is left `Unknown` rather
+/// than aborting the whole archive — chunk0-6 made payloads lazy precisely
+/// so one bad entry doesn't take down browsing/extracting the rest.
+fn expand_nested_archives(entries: &mut Vec<GrpEntry>) -> Result<()> {
+    let mut scan_from = 0usize;
+    loop {
+        let scan_to = entries.len();
+        if scan_from == scan_to {
+            break;
         }
-        //Handles Compression
-        match entry.compression {
-            Compression::Zstd => {
-                let mut dec = zstd::stream::read::Decoder::new(Cursor::new(bytes))?;
-                let mut out = Vec::with_capacity(bytes.len() * 2);
-                dec.read_to_end(&mut out)?;
-                fs::write(&out_path, out)?;
+        for i in scan_from..scan_to {
+            let (start, size, compression, source, full_path) = {
+                let e = &entries[i];
+                (e.start, e.size, e.compression, e.source.clone(), e.full_path.clone())
+            };
+            let Ok(raw) = source.read(start, size) else {
+                entries[i].kind = FileKind::Unknown;
+                continue;
+            };
+            let Ok(probe) = codec::decode_prefix(compression, &raw, CLASSIFY_PROBE_LEN) else {
+                entries[i].kind = FileKind::Unknown;
+                continue;
+            };
+            let kind = detect_kind(&probe);
+            entries[i].kind = kind;
+            if kind != FileKind::Grp2 {
+                continue;
             }
-            Compression::Raw => {
-                fs::write(&out_path, bytes)?;
+            // Confirmed nested archive: worth paying for the full decode.
+            let Ok(decompressed) = decompress(&raw, compression) else {
+                entries[i].kind = FileKind::Unknown;
+                continue;
+            };
+            let nested_source: Rc<dyn ByteSource> = Rc::new(decompressed.clone());
+            let Ok((_, _, nested_entries, _)) = parse_entry_table(&decompressed, nested_source) else {
+                continue;
+            };
+            for mut nested in nested_entries {
+                nested.full_path = format!("{full_path}/{}", nested.full_path);
+                nested.is_nested = true;
+                entries.push(nested);
             }
         }
-        Ok(out_path)
+        scan_from = scan_to;
     }
+    Ok(())
+}
 
-    fn read_entry(&self, entry: &GrpEntry) -> Result<Vec<u8>> {
-        let bytes = &self.file_data[entry.start as usize .. (entry.start + entry.size) as usize];
-        match entry.compression {
-            Compression::Zstd => {
-                let mut dec = zstd::stream::read::Decoder::new(Cursor::new(bytes))?;
-                let mut out = Vec::new();
-                dec.read_to_end(&mut out)?;
-                Ok(out)
-            }
-            Compression::Raw => Ok(bytes.to_vec()),
+/// Decodes an entry's raw (still-compressed) bytes through its detected codec.
+fn decompress(bytes: &[u8], compression: &'static str) -> Result<Vec<u8>> {
+    codec::decode_with(compression, bytes)
+}
+
+/// A `.tfd` payload is a bare BC-compressed block stream with no magic of
+/// its own, so `detect_kind` leaves it `Unknown`. Reclassify it as
+/// `FileKind::Texture` by extension once its `.tfh` header sibling is
+/// confirmed present, since that's the pair `load_tfd_entry` needs to
+/// actually decode it.
+fn reclassify_tfd_pairs(entries: &mut [GrpEntry]) {
+    let tfh_stems: std::collections::BTreeSet<String> = entries
+        .iter()
+        .filter(|e| e.full_path.to_ascii_lowercase().ends_with(".tfh"))
+        .map(|e| e.full_path[..e.full_path.len() - 4].to_ascii_lowercase())
+        .collect();
+    for e in entries.iter_mut() {
+        if e.kind == FileKind::Unknown
+            && e.full_path.to_ascii_lowercase().ends_with(".tfd")
+            && tfh_stems.contains(&e.full_path[..e.full_path.len() - 4].to_ascii_lowercase())
+        {
+            e.kind = FileKind::Texture;
         }
     }
 }
-//Handles getting little-endian 4byte values
-fn get_u32(data: &[u8], off: usize) -> Result<u32> {
-    if off + 4 > data.len() { anyhow::bail!("EOF reading u32 at 0x{off:08X}"); } //doubt this will ever happen but better safe than sorry
-    Ok(u32::from_le_bytes(data[off..off+4].try_into().unwrap()))
-}
 
-fn read_cstr(buf: &[u8], off: usize) -> Result<String> {
-    let mut end = off;
-    while end < buf.len() && buf[end] != 0 { end += 1; }
-    if end == buf.len() { anyhow::bail!("unterminated string at 0x{off:08X}"); }
-    Ok(std::str::from_utf8(&buf[off..end])?.to_string())
+/// Finds `entry`'s `.tfh` header sibling (same path, extension swapped) and
+/// decodes the pair through `tfd::decode`. Both sides go through
+/// `GrpFile::read_entry` like any other payload, so GRP-level compression
+/// and staged overrides are already handled before `tfd::decode` sees them.
+fn load_tfd_entry(pack: &GrpFile, entry: &GrpEntry) -> Result<tfd::TfdImage> {
+    let tfh_path = format!("{}.tfh", &entry.full_path[..entry.full_path.len() - 4]);
+    let tfh_entry = pack
+        .entries
+        .iter()
+        .find(|e| e.full_path.eq_ignore_ascii_case(&tfh_path))
+        .ok_or_else(|| anyhow!("no matching .tfh header for {}", entry.full_path))?;
+    let tfd_bytes = pack.read_entry(entry)?;
+    let tfh_bytes = pack.read_entry(tfh_entry)?;
+    tfd::decode(&tfd_bytes, &tfh_bytes)
 }
 
-
 /* --------------------------- Not quite sure how stuff works past this point, Chat-GiPiTy ui magic --------------------------- */
 
 #[derive(Default)]
@@ -182,6 +445,11 @@ struct AppState {
     message: String,
     mdl_viewer: Option<ModelViewer>,
     mdl_viewer_idx: Option<usize>,
+    tex_viewer: Option<TextureViewer>,
+    tex_viewer_idx: Option<usize>,
+    // (entry index, crc32, md5, sha1) for the currently selected entry, so
+    // the Details panel doesn't rehash on every redraw.
+    digest_cache: Option<(usize, String, String, String)>,
 }
 
 #[derive(Default)]
@@ -220,7 +488,7 @@ impl AppState {
 }
 
 impl eframe::App for AppState {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
             if ui.add(Button::new("Open .grp")).clicked() {
                 if let Some(path) = FileDialog::new().add_filter("GRP", &["grp"]).pick_file() {
@@ -235,6 +503,7 @@ impl eframe::App for AppState {
                             self.pack = Some(pack);
                             self.build_tree();
                             self.selected = None;
+                            self.digest_cache = None;
                         }
                         Err(e) => { self.message = format!("Failed to open: {e:#}"); }
                     }
@@ -262,35 +531,110 @@ impl eframe::App for AppState {
                                                folder.display());
                     }
                 }
+
+                if ui.add(Button::new("Save packed GRP…")).clicked() {
+                    if let Some(path) = FileDialog::new()
+                        .set_file_name("repacked.grp")
+                        .add_filter("GRP", &["grp"])
+                        .save_file()
+                    {
+                        match pack.write(&path) {
+                            Ok(()) => self.message = format!("Wrote packed GRP to {}", path.display()),
+                            Err(err) => self.message = format!("Save packed GRP failed: {err:#}"),
+                        }
+                    }
+                }
+
+                if ui.add(Button::new("Export manifest…")).clicked() {
+                    if let Some(path) = FileDialog::new()
+                        .set_file_name("manifest.tsv")
+                        .add_filter("TSV", &["tsv"])
+                        .save_file()
+                    {
+                        match manifest::build_manifest(pack).and_then(|rows| {
+                            manifest::write_manifest(&path, &rows)?;
+                            Ok(rows.len())
+                        }) {
+                            Ok(n) => self.message = format!("Wrote manifest for {n} files to {}", path.display()),
+                            Err(err) => self.message = format!("Export manifest failed: {err:#}"),
+                        }
+                    }
+                }
+
+                if ui.add(Button::new("Verify against manifest…")).clicked() {
+                    if let Some(path) = FileDialog::new().add_filter("TSV", &["tsv"]).pick_file() {
+                        match manifest::read_manifest(&path).and_then(|rows| manifest::verify_against(pack, &rows)) {
+                            Ok(report) if report.is_clean() => {
+                                self.message = format!("Verified {} files: all match", report.matched);
+                            }
+                            Ok(report) => {
+                                self.message = format!(
+                                    "Verify: {} matched, {} mismatched, {} missing, {} extra (see console)",
+                                    report.matched, report.mismatched.len(), report.missing.len(), report.extra.len()
+                                );
+                                println!("Mismatched: {:?}", report.mismatched);
+                                println!("Missing: {:?}", report.missing);
+                                println!("Extra: {:?}", report.extra);
+                            }
+                            Err(err) => self.message = format!("Verify failed: {err:#}"),
+                        }
+                    }
+                }
             } else {
                 ui.label("Open a .grp to view its contents.");
             }
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            if let (Some(pack), Some(sel)) = (&self.pack, self.selected) {
-                let e = &pack.entries[sel];
+            if let (Some(pack), Some(sel)) = (&mut self.pack, self.selected) {
+                let e = pack.entries[sel].clone();
                 ui.heading("Details");
                 ui.monospace(&e.full_path);
                 ui.separator();
                 ui.label(format!("Index: {}", e.index));
                 ui.label(format!("Start: 0x{:X}", e.start));
                 ui.label(format!("Size: {} bytes", e.size));
-                ui.label(format!("Compression: {:?}", e.compression));
+                ui.label(format!("Compression: {}", e.compression));
+                ui.label(format!("Kind: {} {}", e.kind.icon(), e.kind.label()));
+                if self.digest_cache.as_ref().map(|(idx, ..)| *idx) != Some(sel) {
+                    self.digest_cache = pack.read_entry(&e).ok().map(|bytes| {
+                        let (crc32, md5, sha1) = manifest::hash_bytes(&bytes);
+                        (sel, crc32, md5, sha1)
+                    });
+                }
+                if let Some((_, crc32, md5, sha1)) = &self.digest_cache {
+                    ui.label(format!("CRC32: {crc32}"));
+                    ui.label(format!("MD5: {md5}"));
+                    ui.label(format!("SHA-1: {sha1}"));
+                }
                 if ui.add(Button::new("Extract this file…")).clicked() {
                     if let Some(folder) = FileDialog::new().pick_folder() {
-                        match pack.extract_entry(e, &folder) {
+                        match pack.extract_entry(&e, &folder) {
                             Ok(p) => self.message = format!("Saved {}", p.display()),
                             Err(err) => self.message = format!("Extract failed: {err:#}"),
                         }
                     }
                 }
+                if !pack.is_nested_child(&e) {
+                    if ui.add(Button::new("Replace bytes from file…")).clicked() {
+                        if let Some(path) = FileDialog::new().pick_file() {
+                            match fs::read(&path) {
+                                Ok(bytes) => {
+                                    pack.set_override(&e.full_path, bytes);
+                                    self.digest_cache = None;
+                                    self.message = format!("Replaced {} from {}", e.full_path, path.display());
+                                }
+                                Err(err) => self.message = format!("Failed to read {}: {err:#}", path.display()),
+                            }
+                        }
+                    }
+                }
             }
         });
         // Load model viewer when an MDL file is selected
         if let (Some(pack), Some(sel)) = (&self.pack, self.selected) {
             let entry = &pack.entries[sel];
-            if entry.full_path.to_lowercase().ends_with(".mdl") {
+            if entry.kind == FileKind::Model {
                 if self.mdl_viewer_idx != Some(sel) {
                     match pack.read_entry(entry).and_then(|d| mdl::parse_all_chunks(&d)) {
                         Ok(chunks) => {
@@ -316,6 +660,38 @@ impl eframe::App for AppState {
         if let Some(viewer) = &mut self.mdl_viewer {
             egui::Window::new("Model Viewer")
                 .anchor(Align2::RIGHT_BOTTOM, [0.0, 0.0])
+                .show(ctx, |ui| { viewer.ui(ui, &*frame); });
+        }
+
+        // Load texture viewer when a TFD/TFH texture pair is selected
+        if let (Some(pack), Some(sel)) = (&self.pack, self.selected) {
+            let entry = &pack.entries[sel];
+            if entry.kind == FileKind::Texture {
+                if self.tex_viewer_idx != Some(sel) {
+                    match load_tfd_entry(pack, entry) {
+                        Ok(img) => {
+                            self.tex_viewer = Some(TextureViewer::new(img));
+                            self.tex_viewer_idx = Some(sel);
+                        }
+                        Err(err) => {
+                            self.message = format!("Failed to load texture: {err:#}");
+                            self.tex_viewer = None;
+                            self.tex_viewer_idx = None;
+                        }
+                    }
+                }
+            } else {
+                self.tex_viewer = None;
+                self.tex_viewer_idx = None;
+            }
+        } else {
+            self.tex_viewer = None;
+            self.tex_viewer_idx = None;
+        }
+
+        if let Some(viewer) = &mut self.tex_viewer {
+            egui::Window::new("Texture Viewer")
+                .anchor(Align2::LEFT_BOTTOM, [0.0, 0.0])
                 .show(ctx, |ui| { viewer.ui(ui); });
         }
     }
@@ -328,7 +704,8 @@ fn draw_tree(ui: &mut egui::Ui, node: &TreeNode, pack: &GrpFile, selected: &mut
         });
     }
     for (name, &idx) in &node.files {
-        if ui.selectable_label(*selected == Some(idx), name).clicked() {
+        let label = format!("{} {name}", pack.entries[idx].kind.icon());
+        if ui.selectable_label(*selected == Some(idx), label).clicked() {
             *selected = Some(idx);
         }
     }
@@ -1,5 +1,6 @@
 use anyhow::{Result, anyhow};
 use std::collections::BTreeMap;
+use crate::binreader::BinReader;
 #[derive(Clone, Debug)]
 pub struct MdlChunk {
     pub header_off: usize,
@@ -11,6 +12,13 @@ pub struct MdlChunk {
     pub offs_bytes: [u16;3], // offsets in bytes (header stores 1/256 byte units)
     pub vertices: Vec<[f32;3]>,
     pub indices: Vec<[u32;3]>,
+    /// UV coordinates, parsed from the third vertex-attribute descriptor
+    /// (`codes[2]`/`offs_bytes[2]`) under the same assumption the position
+    /// stream already makes: `fmt_tag` 0x04 means the stream is `f32`-wide,
+    /// 0x05 means `f16`-wide. Empty when `codes[2]` is 0 (no third stream)
+    /// or the descriptor doesn't fit inside `stride` — callers should treat
+    /// an empty `uvs` as "this model has no usable UVs" rather than panic.
+    pub uvs: Vec<[f32;2]>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -42,17 +50,15 @@ fn f16_to_f32(u: u16) -> f32 {
     sign * (2f32).powi(e-15) * (1.0 + (f as f32)/1024.0)
 }
 
-fn read_u32_le(b: &[u8], off: usize) -> u32 {
-    u32::from_le_bytes([b[off], b[off+1], b[off+2], b[off+3]])
-}
-fn read_i16_le(b: &[u8], off: usize) -> i16 {
-    i16::from_le_bytes([b[off], b[off+1]])
-}
-fn read_u16_le(b: &[u8], off: usize) -> u16 {
-    u16::from_le_bytes([b[off], b[off+1]])
-}
-fn read_f32_le(b: &[u8], off: usize) -> f32 {
-    f32::from_le_bytes([b[off], b[off+1], b[off+2], b[off+3]])
+/// Cheap structural probe for the MDL chunk header signature at offset 0,
+/// without running the full chunk scan. Mirrors the `sig_ok`/`fmt_ok`/
+/// `stride_ok` checks in `parse_all_chunks`.
+pub fn looks_like_mdl(bytes: &[u8]) -> bool {
+    bytes.len() >= 0x110
+        && bytes.o_u8(0x9C) == Some(1)
+        && bytes.o_u8(0x9D) == Some(0)
+        && matches!(bytes.o_u8(0x9E), Some(0x04) | Some(0x05))
+        && bytes.o_u8(0x9F).is_some_and(|s| (4..=64).contains(&s))
 }
 
 pub fn parse_all_chunks(bytes: &[u8]) -> Result<Vec<MdlChunk>> {
@@ -60,26 +66,26 @@ pub fn parse_all_chunks(bytes: &[u8]) -> Result<Vec<MdlChunk>> {
     let mut off = 0usize;
     while off + 0x110 <= bytes.len() {
         // Heuristic: header at 'off' if [0x9C]=1, [0x9D]=0 and fmt is 4/5 with reasonable stride
-        let sig_ok = bytes[off + 0x9C] == 1 && bytes[off + 0x9D] == 0;
-        let fmt_tag = bytes[off + 0x9E];
-        let stride = bytes[off + 0x9F];
+        let sig_ok = bytes.c_u8(off + 0x9C)? == 1 && bytes.c_u8(off + 0x9D)? == 0;
+        let fmt_tag = bytes.c_u8(off + 0x9E)?;
+        let stride = bytes.c_u8(off + 0x9F)?;
         let fmt_ok = fmt_tag == 0x04 || fmt_tag == 0x05;
         let stride_ok = (4..=64).contains(&stride);
         if sig_ok && fmt_ok && stride_ok {
             // Read counts
-            let vcount = read_u32_le(bytes, off + 0xA4);
-            let icount = read_u32_le(bytes, off + 0xA8);
+            let vcount = bytes.c_u32(off + 0xA4)?;
+            let icount = bytes.c_u32(off + 0xA8)?;
             let vaddr = off + 0x110;
             let iaddr = vaddr.checked_add(stride as usize * vcount as usize).unwrap_or(usize::MAX);
             let iend  = iaddr.checked_add(2 * icount as usize).unwrap_or(usize::MAX);
             if iend <= bytes.len() && vaddr < bytes.len() && iaddr <= bytes.len() {
                 // Read descriptor codes and offsets (convert to bytes by >>8)
-                let code0 = read_u16_le(bytes, off + 0x64);
-                let off0b = (read_u16_le(bytes, off + 0x66) >> 8) as u16;
-                let code1 = read_u16_le(bytes, off + 0x68);
-                let off1b = (read_u16_le(bytes, off + 0x6A) >> 8) as u16;
-                let code2 = read_u16_le(bytes, off + 0x6C);
-                let off2b = (read_u16_le(bytes, off + 0x6E) >> 8) as u16;
+                let code0 = bytes.c_u16(off + 0x64)?;
+                let off0b = (bytes.c_u16(off + 0x66)? >> 8) as u16;
+                let code1 = bytes.c_u16(off + 0x68)?;
+                let off1b = (bytes.c_u16(off + 0x6A)? >> 8) as u16;
+                let code2 = bytes.c_u16(off + 0x6C)?;
+                let off2b = (bytes.c_u16(off + 0x6E)? >> 8) as u16;
                 let codes = [code0, code1, code2];
                 let offs_bytes = [off0b, off1b, off2b];
                 // Read vertices (positions only for preview)
@@ -87,24 +93,43 @@ pub fn parse_all_chunks(bytes: &[u8]) -> Result<Vec<MdlChunk>> {
                 for i in 0..(vcount as usize) {
                     let base = vaddr + i*stride as usize;
                     let pos = if fmt_tag == 0x04 {
-                        [ read_f32_le(bytes, base + 0),
-                          read_f32_le(bytes, base + 4),
-                          read_f32_le(bytes, base + 8) ]
+                        [ bytes.c_f32(base + 0)?,
+                          bytes.c_f32(base + 4)?,
+                          bytes.c_f32(base + 8)? ]
                     } else {
-                        let x = f16_to_f32(read_u16_le(bytes, base+0));
-                        let y = f16_to_f32(read_u16_le(bytes, base+2));
-                        let z = f16_to_f32(read_u16_le(bytes, base+4));
+                        let x = f16_to_f32(bytes.c_u16(base+0)?);
+                        let y = f16_to_f32(bytes.c_u16(base+2)?);
+                        let z = f16_to_f32(bytes.c_u16(base+4)?);
                         [x,y,z]
                     };
                     vertices.push(pos);
                 }
+                // UV stream: third descriptor slot, same width convention as
+                // the position stream above. `codes[2] == 0` means this
+                // model has no third stream at all.
+                let uv_size = if fmt_tag == 0x04 { 8usize } else { 4usize };
+                let uvs = if codes[2] != 0 && off2b as usize + uv_size <= stride as usize {
+                    let uv_off = off2b as usize;
+                    (0..vcount as usize)
+                        .map(|i| {
+                            let base = vaddr + i * stride as usize + uv_off;
+                            if fmt_tag == 0x04 {
+                                Ok([bytes.c_f32(base)?, bytes.c_f32(base + 4)?])
+                            } else {
+                                Ok([f16_to_f32(bytes.c_u16(base)?), f16_to_f32(bytes.c_u16(base + 2)?)])
+                            }
+                        })
+                        .collect::<Result<Vec<_>>>()?
+                } else {
+                    Vec::new()
+                };
                 // Read indices
                 let mut indices: Vec<[u32;3]> = Vec::with_capacity(icount as usize / 3);
                 let mut j = 0usize;
                 while j + 6 <= (2*icount as usize) {
-                    let a = read_u16_le(bytes, iaddr + j) as u32;
-                    let b = read_u16_le(bytes, iaddr + j + 2) as u32;
-                    let c = read_u16_le(bytes, iaddr + j + 4) as u32;
+                    let a = bytes.c_u16(iaddr + j)? as u32;
+                    let b = bytes.c_u16(iaddr + j + 2)? as u32;
+                    let c = bytes.c_u16(iaddr + j + 4)? as u32;
                     indices.push([a,b,c]);
                     j += 6;
                 }
@@ -112,7 +137,7 @@ pub fn parse_all_chunks(bytes: &[u8]) -> Result<Vec<MdlChunk>> {
                     header_off: off,
                     stride, fmt_tag, vcount, icount,
                     codes, offs_bytes,
-                    vertices, indices
+                    vertices, indices, uvs,
                 });
                 // Jump near end of this chunk to continue scanning
                 off = iend;
@@ -1,6 +1,19 @@
-use egui::{self, ColorImage, ComboBox, Response, TextureHandle, Vec2};
-use std::collections::BTreeSet;
+use egui::{self, Button, ColorImage, ComboBox, Response, TextureHandle, Vec2};
+use rfd::FileDialog;
+use std::collections::{BTreeMap, BTreeSet};
 use crate::mdl::{MdlChunk, group_models, ModelGroup};
+use crate::tfd::{self, TfdPixels};
+#[cfg(feature = "wgpu-renderer")]
+use crate::gpu_render::{self, GpuRenderer};
+
+/// Which pass(es) the CPU fallback renders. The GPU path always shades
+/// solid (see `mdl_viewer.wgsl`); this only affects `render_current`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ShadeMode {
+    Wireframe,
+    Solid,
+    Both,
+}
 
 pub struct ModelViewer {
     // Data
@@ -9,12 +22,28 @@ pub struct ModelViewer {
     pub selected_group: usize,
     pub selected_lod: usize,
 
-    // Render state
+    // CPU fallback render state (wireframe and/or solid, per `mode`)
     tex: Option<TextureHandle>,
     buf: ColorImage,
     yaw: f32,
     pitch: f32,
     dist: f32,
+    mode: ShadeMode,
+
+    /// TFD textures loaded via "Load texture…", flattened to their top RGBA
+    /// mip (layer 0) since the CPU rasterizer below has no mip/layer
+    /// selection of its own.
+    textures: Vec<LoadedTexture>,
+    /// Model group index -> index into `textures`, so the rasterizer knows
+    /// which texture (if any) to sample while drawing the current group.
+    group_texture: BTreeMap<usize, usize>,
+    message: String,
+
+    /// Lazily created the first time a wgpu-backed `eframe::Frame` is seen.
+    /// Stays `None` (falling back to the CPU path above) when the feature
+    /// is off or the frame isn't wgpu-backed.
+    #[cfg(feature = "wgpu-renderer")]
+    gpu: Option<GpuRenderer>,
 }
 
 impl ModelViewer {
@@ -30,10 +59,36 @@ impl ModelViewer {
             yaw: 0.5,
             pitch: 0.2,
             dist: 3.0,
+            mode: ShadeMode::Both,
+            textures: Vec::new(),
+            group_texture: BTreeMap::new(),
+            message: String::new(),
+            #[cfg(feature = "wgpu-renderer")]
+            gpu: None,
+        }
+    }
+
+    /// Prompts for a `.tfd` file plus its sibling `.tfh` (same stem, same
+    /// folder) and decodes them into a `LoadedTexture`. Mirrors the
+    /// `FileDialog` pattern `TextureViewer`'s export button uses, just for
+    /// opening a pair of files instead of saving one.
+    fn load_texture(&mut self) {
+        let Some(tfd_path) = FileDialog::new().add_filter("TFD", &["tfd"]).pick_file() else {
+            return;
+        };
+        let tfh_path = tfd_path.with_extension("tfh");
+        match LoadedTexture::load(&tfd_path, &tfh_path) {
+            Ok(tex) => {
+                self.group_texture.insert(self.selected_group, self.textures.len());
+                self.textures.push(tex);
+                self.message.clear();
+            }
+            Err(err) => self.message = format!("Load failed: {err:#}"),
         }
     }
 
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
+    #[cfg_attr(not(feature = "wgpu-renderer"), allow(unused_variables))]
+    pub fn ui(&mut self, ui: &mut egui::Ui, frame: &eframe::Frame) {
         ui.set_min_width(512.0);
         ui.set_max_width(512.0);
 
@@ -67,8 +122,72 @@ impl ModelViewer {
                     }
                 }
             });
+        ComboBox::from_label("Shading")
+            .selected_text(match self.mode {
+                ShadeMode::Wireframe => "Wireframe",
+                ShadeMode::Solid => "Solid",
+                ShadeMode::Both => "Both",
+            })
+            .show_ui(ui, |ui| {
+                for (label, mode) in [
+                    ("Wireframe", ShadeMode::Wireframe),
+                    ("Solid", ShadeMode::Solid),
+                    ("Both", ShadeMode::Both),
+                ] {
+                    if ui.selectable_value(&mut self.mode, mode, label).clicked() {
+                        ui.ctx().request_repaint();
+                    }
+                }
+            });
+
+        if ui.add(Button::new("Load texture…")).clicked() {
+            self.load_texture();
+            ui.ctx().request_repaint();
+        }
+        if !self.textures.is_empty() {
+            let current = self.group_texture.get(&self.selected_group).copied();
+            ComboBox::from_label("Texture")
+                .selected_text(current.map_or("None", |idx| self.textures[idx].name.as_str()))
+                .show_ui(ui, |ui| {
+                    if ui.selectable_label(current.is_none(), "None").clicked() {
+                        self.group_texture.remove(&self.selected_group);
+                        ui.ctx().request_repaint();
+                    }
+                    for (idx, tex) in self.textures.iter().enumerate() {
+                        if ui.selectable_label(current == Some(idx), &tex.name).clicked() {
+                            self.group_texture.insert(self.selected_group, idx);
+                            ui.ctx().request_repaint();
+                        }
+                    }
+                });
+        }
+        if !self.message.is_empty() {
+            ui.label(&self.message);
+        }
 
         let size = Vec2::splat(512.0);
+
+        #[cfg(feature = "wgpu-renderer")]
+        if let Some(render_state) = frame.wgpu_render_state() {
+            if self.gpu.is_none() {
+                self.gpu = GpuRenderer::new(render_state, [512, 512]);
+            }
+            let mvp = self.mvp();
+            if let Some(gpu) = &mut self.gpu {
+                let ch = {
+                    let g = &self.groups[self.selected_group];
+                    &self.chunks[g.lods[self.selected_lod]]
+                };
+                let texture_id = gpu.render(render_state, self.selected_group, self.selected_lod, ch, mvp);
+                let resp = ui
+                    .add(egui::Image::new((texture_id, size)).sense(egui::Sense::drag()))
+                    .on_hover_cursor(egui::CursorIcon::Grab);
+                self.handle_input(ui, &resp);
+                return;
+            }
+        }
+
+        // CPU fallback: wgpu-renderer is off or the frame isn't wgpu-backed.
         self.render_current();
         let tex = self
             .tex
@@ -80,6 +199,19 @@ impl ModelViewer {
         self.handle_input(ui, &resp);
     }
 
+    /// Orbit-camera model-view-projection matrix for the GPU path, driven
+    /// by the same yaw/pitch/dist the CPU rasterizer's custom projection
+    /// uses below.
+    #[cfg(feature = "wgpu-renderer")]
+    fn mvp(&self) -> [[f32; 4]; 4] {
+        let proj = gpu_render::mat4_perspective(0.9, 1.0, 0.05, 100.0);
+        let view = gpu_render::mat4_mul(
+            &gpu_render::mat4_translate(0.0, 0.0, -(self.dist * 2.0 + 1.0)),
+            &gpu_render::mat4_mul(&gpu_render::mat4_rotate_x(self.pitch), &gpu_render::mat4_rotate_y(self.yaw)),
+        );
+        gpu_render::mat4_mul(&proj, &view)
+    }
+
     fn handle_input(&mut self, ui: &egui::Ui, resp: &Response) {
         if resp.dragged() {
             let d = resp.drag_delta();
@@ -102,9 +234,141 @@ impl ModelViewer {
             let g = &self.groups[self.selected_group];
             &self.chunks[g.lods[self.selected_lod]]
         };
+        self.buf.pixels.fill(egui::Color32::from_rgb(15, 15, 20));
+
+        let texture = self
+            .group_texture
+            .get(&self.selected_group)
+            .map(|&idx| &self.textures[idx])
+            .filter(|_| ch.uvs.len() == ch.vertices.len());
+
+        let (sy, cy) = self.yaw.sin_cos();
+        let (sp, cp) = self.pitch.sin_cos();
+        let verts: Vec<Projected> = ch
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| {
+                let x = p[0] * cy + p[2] * sy;
+                let z0 = -p[0] * sy + p[2] * cy;
+                let y = p[1] * cp - z0 * sp;
+                let z = p[1] * sp + z0 * cp;
+                let zc = (z + self.dist * 2.0 + 1.0).max(0.01);
+                let f = 300.0 / zc;
+                let inv_z = 1.0 / zc;
+                let uv_over_z = if texture.is_some() {
+                    let uv = ch.uvs[i];
+                    [uv[0] * inv_z, uv[1] * inv_z]
+                } else {
+                    [0.0, 0.0]
+                };
+                Projected {
+                    cam: [x, y, zc],
+                    screen: [(self.buf.size[0] as f32) / 2.0 + x * f, (self.buf.size[1] as f32) / 2.0 - y * f],
+                    inv_z,
+                    uv_over_z,
+                }
+            })
+            .collect();
+
+        if matches!(self.mode, ShadeMode::Solid | ShadeMode::Both) {
+            self.rasterize_solid(ch, &verts, texture);
+        }
+        if matches!(self.mode, ShadeMode::Wireframe | ShadeMode::Both) {
+            self.draw_wireframe(ch, &verts);
+        }
+    }
+
+    /// Edge-function scanline fill with a per-frame z-buffer: for each
+    /// triangle, skip it if its screen-space signed area is zero
+    /// (degenerate), then for every pixel in its bounding box interpolate
+    /// `1/z` across the edge-function barycentric weights and keep it only
+    /// if it's nearer than what's already in `depth`. Back-facing triangles
+    /// are not culled; both windings are filled and the z-buffer decides
+    /// visibility.
+    ///
+    /// When `texture` is `Some` (the current group has one assigned and the
+    /// chunk has matching UVs), the base color comes from sampling it:
+    /// `u/z`, `v/z` and `1/z` are barycentric-interpolated like the rest of
+    /// this function's attributes, then `u = (u/z)/(1/z)` recovers the
+    /// perspective-correct UV right before the sample. Without a texture,
+    /// shading is flat, using the face normal from two camera-space edges —
+    /// `MdlChunk` carries no per-vertex normals, same reason the GPU path
+    /// derives one from screen-space derivatives instead. Either way the
+    /// result is modulated by the same directional-light term, so a
+    /// textured and untextured model read as part of the same renderer.
+    fn rasterize_solid(&mut self, ch: &MdlChunk, verts: &[Projected], texture: Option<&LoadedTexture>) {
         let w = self.buf.size[0] as i32;
         let h = self.buf.size[1] as i32;
-        self.buf.pixels.fill(egui::Color32::from_rgb(15, 15, 20));
+        let mut depth = vec![f32::INFINITY; (w * h) as usize];
+        const LIGHT_DIR: [f32; 3] = [0.4, 0.7, 0.6];
+
+        for tri in &ch.indices {
+            let (v0, v1, v2) = (&verts[tri[0] as usize], &verts[tri[1] as usize], &verts[tri[2] as usize]);
+            // MDL winding order relative to this y-down screen space isn't
+            // established (the GPU path leaves `cull_mode: None` for the same
+            // reason), so don't guess a front-facing sign here either — just
+            // skip degenerate triangles and let the z-buffer below sort out
+            // visibility regardless of which way a triangle winds.
+            let area = edge(v0.screen, v1.screen, v2.screen);
+            if area == 0.0 {
+                continue;
+            }
+
+            let normal = normalize(cross(sub(v1.cam, v0.cam), sub(v2.cam, v0.cam)));
+            let ndotl = (normal[0] * LIGHT_DIR[0] + normal[1] * LIGHT_DIR[1] + normal[2] * LIGHT_DIR[2]).max(0.0);
+            let shade = 0.25 + 0.75 * ndotl;
+            let flat_color = [190.0 / 255.0, 195.0 / 255.0, 205.0 / 255.0];
+
+            let min_x = v0.screen[0].min(v1.screen[0]).min(v2.screen[0]).floor().max(0.0) as i32;
+            let max_x = v0.screen[0].max(v1.screen[0]).max(v2.screen[0]).ceil().min((w - 1) as f32) as i32;
+            let min_y = v0.screen[1].min(v1.screen[1]).min(v2.screen[1]).floor().max(0.0) as i32;
+            let max_y = v0.screen[1].max(v1.screen[1]).max(v2.screen[1]).ceil().min((h - 1) as f32) as i32;
+
+            for py in min_y..=max_y {
+                for px in min_x..=max_x {
+                    let p = [px as f32 + 0.5, py as f32 + 0.5];
+                    let w0 = edge(v1.screen, v2.screen, p);
+                    let w1 = edge(v2.screen, v0.screen, p);
+                    let w2 = edge(v0.screen, v1.screen, p);
+                    // Inside-triangle edge values share the sign of `area`
+                    // whichever way the triangle winds; check against that
+                    // sign instead of assuming positive (which is what used
+                    // to make the `area <= 0.0` cull above necessary).
+                    let inside = if area > 0.0 {
+                        w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0
+                    } else {
+                        w0 <= 0.0 && w1 <= 0.0 && w2 <= 0.0
+                    };
+                    if !inside {
+                        continue;
+                    }
+                    let (w0, w1, w2) = (w0 / area, w1 / area, w2 / area);
+                    let inv_z = w0 * v0.inv_z + w1 * v1.inv_z + w2 * v2.inv_z;
+                    let z = 1.0 / inv_z;
+                    let idx = (py * w + px) as usize;
+                    if z < depth[idx] {
+                        depth[idx] = z;
+                        let base = match texture {
+                            Some(tex) => {
+                                let u_over_z = w0 * v0.uv_over_z[0] + w1 * v1.uv_over_z[0] + w2 * v2.uv_over_z[0];
+                                let v_over_z = w0 * v0.uv_over_z[1] + w1 * v1.uv_over_z[1] + w2 * v2.uv_over_z[1];
+                                tex.sample(u_over_z / inv_z, v_over_z / inv_z)
+                            }
+                            None => flat_color,
+                        };
+                        self.buf[(px as usize, py as usize)] = egui::Color32::from_rgb(
+                            (base[0] * 255.0 * shade) as u8,
+                            (base[1] * 255.0 * shade) as u8,
+                            (base[2] * 255.0 * shade) as u8,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw_wireframe(&mut self, ch: &MdlChunk, verts: &[Projected]) {
         let mut edges: BTreeSet<(u32, u32)> = BTreeSet::new();
         for tri in &ch.indices {
             let (a, b, c) = (tri[0], tri[1], tri[2]);
@@ -113,24 +377,10 @@ impl ModelViewer {
             edges.insert(e(b, c));
             edges.insert(e(c, a));
         }
-        let (sy, cy) = self.yaw.sin_cos();
-        let (sp, cp) = self.pitch.sin_cos();
-        let mut screen: Vec<[i32; 2]> = Vec::with_capacity(ch.vertices.len());
-        for &p in &ch.vertices {
-            let mut x = p[0] * cy + p[2] * sy;
-            let mut z = -p[0] * sy + p[2] * cy;
-            let mut y = p[1] * cp - z * sp;
-            z = p[1] * sp + z * cp;
-            let zc = z + self.dist * 2.0 + 1.0;
-            let f = 300.0 / zc.max(0.01);
-            let sx = (w / 2) as f32 + x * f;
-            let sy2 = (h / 2) as f32 - y * f;
-            screen.push([sx as i32, sy2 as i32]);
-        }
         for (a, b) in edges {
-            let pa = screen[a as usize];
-            let pb = screen[b as usize];
-            self.line(pa[0], pa[1], pb[0], pb[1], egui::Color32::WHITE);
+            let pa = verts[a as usize].screen;
+            let pb = verts[b as usize].screen;
+            self.line(pa[0] as i32, pa[1] as i32, pb[0] as i32, pb[1] as i32, egui::Color32::WHITE);
         }
     }
 
@@ -166,3 +416,80 @@ impl ModelViewer {
     }
 }
 
+/// A texture loaded via "Load texture…", flattened to its top mip's RGBA
+/// bytes (layer 0) up front so sampling during rasterization is a plain
+/// array index rather than a match on `TfdPixels` per pixel.
+struct LoadedTexture {
+    name: String,
+    width: usize,
+    height: usize,
+    rgba: Vec<u8>,
+}
+
+impl LoadedTexture {
+    fn load(tfd_path: &std::path::Path, tfh_path: &std::path::Path) -> anyhow::Result<Self> {
+        let tfd_bytes = std::fs::read(tfd_path)?;
+        let tfh_bytes = std::fs::read(tfh_path)?;
+        let img = tfd::decode(&tfd_bytes, &tfh_bytes)?;
+        let (width, height, rgba) = match &img.pixels {
+            TfdPixels::Rgba8 { layers } => {
+                let top = &layers[0][0];
+                (top.width, top.height, top.rgba.clone())
+            }
+            TfdPixels::Hdr { layers } => {
+                let top = &layers[0][0];
+                (top.width, top.height, tfd::expose(&top.rgb))
+            }
+        };
+        let name = tfd_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "texture".to_string());
+        Ok(Self { name, width, height, rgba })
+    }
+
+    /// Nearest-neighbor sample with wraparound UVs; matches the rasterizer's
+    /// other approximations (flat shading, no mipmapping) rather than
+    /// spending the per-pixel cost of bilinear filtering.
+    fn sample(&self, u: f32, v: f32) -> [f32; 3] {
+        let x = (u.rem_euclid(1.0) * self.width as f32) as usize;
+        let y = (v.rem_euclid(1.0) * self.height as f32) as usize;
+        let x = x.min(self.width - 1);
+        let y = y.min(self.height - 1);
+        let o = (y * self.width + x) * 4;
+        [self.rgba[o] as f32 / 255.0, self.rgba[o + 1] as f32 / 255.0, self.rgba[o + 2] as f32 / 255.0]
+    }
+}
+
+/// A vertex's camera-space position (used for face normals), screen-space
+/// position, 1/z, and UV/z (used for perspective-correct texture sampling)
+/// — computed once per frame and shared between `rasterize_solid` and
+/// `draw_wireframe`. `uv_over_z` is `[0.0, 0.0]` when the current model has
+/// no (or mismatched) UV data; it's simply never read in that case.
+struct Projected {
+    cam: [f32; 3],
+    screen: [f32; 2],
+    inv_z: f32,
+    uv_over_z: [f32; 2],
+}
+
+/// Signed area of the triangle `a, b, c`, and (per Pineda's edge-function
+/// algorithm) the unnormalized barycentric weight of the vertex opposite
+/// `a`-`b` when `c` is replaced by a test point `p`.
+fn edge(a: [f32; 2], b: [f32; 2], p: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt().max(1e-6);
+    [v[0] / len, v[1] / len, v[2] / len]
+}
+
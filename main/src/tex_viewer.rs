@@ -1,21 +1,185 @@
-use egui::{ColorImage, TextureHandle, Vec2};
-use crate::tfd::TfdImage;
+use egui::{Button, ColorImage, ComboBox, TextureHandle, Vec2};
+use rfd::FileDialog;
+use crate::tfd::{expose, TfdImage, TfdPixels};
+
+/// How the decoded RGBA bytes are mapped to what's shown on screen.
+/// `RawChannels` is the decoded bytes untouched, same as `Rgba` for normal
+/// color textures — the distinct label matters for BC5 data, where R/G hold
+/// tangent-space normal X/Y rather than color, so "RGBA" is misleading but
+/// "raw channels" still describes what's on screen.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DisplayMode {
+    Rgba,
+    RawChannels,
+    ReconstructedNormals,
+    ChannelR,
+    ChannelG,
+    ChannelB,
+    ChannelA,
+}
+
+/// A single decoded mip level, already flattened to LDR RGBA (HDR levels are
+/// clamp-exposed once up front so display-mode logic never needs to care).
+struct Level {
+    width: usize,
+    height: usize,
+    rgba: Vec<u8>,
+}
+
+/// Which on-disk format the "Export…" button writes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Png,
+    Dds,
+    Exr,
+}
 
 pub struct TextureViewer {
+    /// Kept around (alongside the `layers` preview cache below) so "Export…"
+    /// can hand off to `TfdImage`'s lossless DDS path, which needs the
+    /// original compressed bytes that `layers` doesn't retain.
+    img: TfdImage,
+    /// `[layer][mip]`; layer 0 only for a plain 2D texture, one entry per
+    /// face for a cubemap, one per slice for a texture array.
+    layers: Vec<Vec<Level>>,
+    layer: usize,
+    mip: usize,
+    mode: DisplayMode,
     image: ColorImage,
     tex: Option<TextureHandle>,
+    export_format: ExportFormat,
+    message: String,
 }
 
 impl TextureViewer {
     pub fn new(img: TfdImage) -> Self {
-        let image = ColorImage::from_rgba_unmultiplied(
-            [img.width, img.height],
-            &img.rgba,
-        );
-        Self { image, tex: None }
+        let layers = Self::build_levels(&img.pixels);
+        let mode = DisplayMode::Rgba;
+        let image = Self::render(&layers[0][0], mode);
+        Self {
+            img,
+            layers,
+            layer: 0,
+            mip: 0,
+            mode,
+            image,
+            tex: None,
+            export_format: ExportFormat::Png,
+            message: String::new(),
+        }
+    }
+
+    fn build_levels(pixels: &TfdPixels) -> Vec<Vec<Level>> {
+        match pixels {
+            TfdPixels::Rgba8 { layers } => layers
+                .iter()
+                .map(|mips| mips.iter().map(|l| Level { width: l.width, height: l.height, rgba: l.rgba.clone() }).collect())
+                .collect(),
+            TfdPixels::Hdr { layers } => layers
+                .iter()
+                .map(|mips| mips.iter().map(|l| Level { width: l.width, height: l.height, rgba: expose(&l.rgb) }).collect())
+                .collect(),
+        }
+    }
+
+    fn current(&self) -> &Level {
+        &self.layers[self.layer][self.mip]
     }
 
     pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+
+        if self.layers.len() > 1 {
+            ComboBox::from_label("Layer")
+                .selected_text(format!("Layer {}", self.layer))
+                .show_ui(ui, |ui| {
+                    for idx in 0..self.layers.len() {
+                        if ui.selectable_value(&mut self.layer, idx, format!("Layer {idx}")).clicked() {
+                            self.mip = self.mip.min(self.layers[self.layer].len() - 1);
+                            changed = true;
+                        }
+                    }
+                });
+        }
+
+        let mip_count = self.layers[self.layer].len();
+        if mip_count > 1 {
+            ComboBox::from_label("Mip")
+                .selected_text(format!("Mip {} ({}x{})", self.mip, self.current().width, self.current().height))
+                .show_ui(ui, |ui| {
+                    for idx in 0..mip_count {
+                        let (w, h) = (self.layers[self.layer][idx].width, self.layers[self.layer][idx].height);
+                        if ui.selectable_value(&mut self.mip, idx, format!("Mip {idx} ({w}x{h})")).clicked() {
+                            changed = true;
+                        }
+                    }
+                });
+        }
+
+        ComboBox::from_label("Display")
+            .selected_text(match self.mode {
+                DisplayMode::Rgba => "RGBA",
+                DisplayMode::RawChannels => "Raw channels",
+                DisplayMode::ReconstructedNormals => "Reconstructed normals",
+                DisplayMode::ChannelR => "Channel R",
+                DisplayMode::ChannelG => "Channel G",
+                DisplayMode::ChannelB => "Channel B",
+                DisplayMode::ChannelA => "Channel A",
+            })
+            .show_ui(ui, |ui| {
+                for (label, mode) in [
+                    ("RGBA", DisplayMode::Rgba),
+                    ("Raw channels", DisplayMode::RawChannels),
+                    ("Reconstructed normals", DisplayMode::ReconstructedNormals),
+                    ("Channel R", DisplayMode::ChannelR),
+                    ("Channel G", DisplayMode::ChannelG),
+                    ("Channel B", DisplayMode::ChannelB),
+                    ("Channel A", DisplayMode::ChannelA),
+                ] {
+                    if ui.selectable_value(&mut self.mode, mode, label).clicked() {
+                        changed = true;
+                    }
+                }
+            });
+
+        if changed {
+            self.image = Self::render(self.current(), self.mode);
+        }
+
+        ComboBox::from_label("Export as")
+            .selected_text(match self.export_format {
+                ExportFormat::Png => "PNG",
+                ExportFormat::Dds => "DDS",
+                ExportFormat::Exr => "OpenEXR",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.export_format, ExportFormat::Png, "PNG");
+                ui.selectable_value(&mut self.export_format, ExportFormat::Dds, "DDS");
+                ui.selectable_value(&mut self.export_format, ExportFormat::Exr, "OpenEXR");
+            });
+
+        if ui.add(Button::new("Export…")).clicked() {
+            let (ext, filter, name) = match self.export_format {
+                ExportFormat::Png => ("png", "PNG", "texture.png"),
+                ExportFormat::Dds => ("dds", "DDS", "texture.dds"),
+                ExportFormat::Exr => ("exr", "OpenEXR", "texture.exr"),
+            };
+            if let Some(path) = FileDialog::new().set_file_name(name).add_filter(filter, &[ext]).save_file() {
+                let result = match self.export_format {
+                    ExportFormat::Png => self.img.export_png(self.layer, &path),
+                    ExportFormat::Dds => self.img.export_dds(&path),
+                    ExportFormat::Exr => self.img.export_exr(self.layer, &path),
+                };
+                self.message = match result {
+                    Ok(()) => format!("Wrote {}", path.display()),
+                    Err(err) => format!("Export failed: {err:#}"),
+                };
+            }
+        }
+        if !self.message.is_empty() {
+            ui.label(&self.message);
+        }
+
         let tex = self.tex.get_or_insert_with(|| {
             ui.ctx()
                 .load_texture("tfd_view", self.image.clone(), egui::TextureOptions::LINEAR)
@@ -30,4 +194,43 @@ impl TextureViewer {
             ui.image((tex.id(), display));
         });
     }
+
+    fn render(level: &Level, mode: DisplayMode) -> ColorImage {
+        let (width, height, rgba) = (level.width, level.height, &level.rgba);
+        match mode {
+            DisplayMode::Rgba | DisplayMode::RawChannels => {
+                ColorImage::from_rgba_unmultiplied([width, height], rgba)
+            }
+            DisplayMode::ReconstructedNormals => {
+                let mut out = vec![0u8; rgba.len()];
+                for (src, dst) in rgba.chunks_exact(4).zip(out.chunks_exact_mut(4)) {
+                    let nx = 2.0 * (src[0] as f32 / 255.0) - 1.0;
+                    let ny = 2.0 * (src[1] as f32 / 255.0) - 1.0;
+                    let nz = (1.0 - nx * nx - ny * ny).max(0.0).sqrt();
+                    dst[0] = src[0];
+                    dst[1] = src[1];
+                    dst[2] = ((nz * 0.5 + 0.5) * 255.0) as u8;
+                    dst[3] = 255;
+                }
+                ColorImage::from_rgba_unmultiplied([width, height], &out)
+            }
+            DisplayMode::ChannelR | DisplayMode::ChannelG | DisplayMode::ChannelB | DisplayMode::ChannelA => {
+                let channel = match mode {
+                    DisplayMode::ChannelR => 0,
+                    DisplayMode::ChannelG => 1,
+                    DisplayMode::ChannelB => 2,
+                    _ => 3,
+                };
+                let mut out = vec![0u8; rgba.len()];
+                for (src, dst) in rgba.chunks_exact(4).zip(out.chunks_exact_mut(4)) {
+                    let v = src[channel];
+                    dst[0] = v;
+                    dst[1] = v;
+                    dst[2] = v;
+                    dst[3] = 255;
+                }
+                ColorImage::from_rgba_unmultiplied([width, height], &out)
+            }
+        }
+    }
 }
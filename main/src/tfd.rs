@@ -1,18 +1,60 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use std::{borrow::Cow, io::Cursor};
 
-/// Decoded texture data from a TFD/TFH pair.
-pub struct TfdImage {
+/// One decoded mip level.
+pub struct Rgba8Level {
     pub width: usize,
     pub height: usize,
     pub rgba: Vec<u8>,
 }
 
+/// One decoded HDR mip level (BC6H: half-float RGB, no alpha).
+pub struct HdrLevel {
+    pub width: usize,
+    pub height: usize,
+    pub rgb: Vec<f32>,
+}
+
+/// Decoded pixel data from a TFD/TFH pair. `layers` holds one mip chain (top
+/// level first) per face/slice — length 1 for a plain 2D texture, 6 for a
+/// cubemap, N for a texture array. BC6H is HDR and can't be clamped into the
+/// 8-bit `Rgba8` case without losing range, so it gets its own variant.
+pub enum TfdPixels {
+    Rgba8 { layers: Vec<Vec<Rgba8Level>> },
+    Hdr { layers: Vec<Vec<HdrLevel>> },
+}
+
+/// Decoded texture data from a TFD/TFH pair, plus the layout metadata and
+/// original compressed bytes needed to re-wrap the texture losslessly (see
+/// `export_dds`) rather than just preview it.
+pub struct TfdImage {
+    pub format: BcFormat,
+    pub top: usize,
+    pub mips: usize,
+    pub layer_count: usize,
+    pub raw: Vec<u8>,
+    pub pixels: TfdPixels,
+}
+
+/// Clamp-exposes an HDR RGB buffer to LDR RGBA; there's no HDR display path,
+/// so this is also how a BC6H level gets previewed or exported as PNG.
+pub(crate) fn expose(rgb: &[f32]) -> Vec<u8> {
+    let mut rgba = vec![0u8; (rgb.len() / 3) * 4];
+    for (px, c) in rgb.chunks_exact(3).enumerate() {
+        let o = px * 4;
+        rgba[o] = (c[0].clamp(0.0, 1.0) * 255.0) as u8;
+        rgba[o + 1] = (c[1].clamp(0.0, 1.0) * 255.0) as u8;
+        rgba[o + 2] = (c[2].clamp(0.0, 1.0) * 255.0) as u8;
+        rgba[o + 3] = 255;
+    }
+    rgba
+}
+
 /// Decode a TFD data stream with help from the accompanying TFH header.
 ///
-/// Supports raw BC1/BC3 textures and container-compressed streams (zstd
-/// compressed) which typically hold BC5 normal maps. Only the top mip level is
-/// expanded to RGBA pixels.
+/// Supports BC1/BC3/BC5/BC6H/BC7 textures, both raw and container-compressed
+/// (zstd) streams, expanding every mip level (and face/slice, for cubemaps
+/// and arrays) rather than just the top one.
 pub fn decode(tfd: &[u8], tfh: &[u8]) -> Result<TfdImage> {
     // Tile-compressed TFDs aren't multiples of 8 bytes. Those streams are
     // zstd-compressed; decode them into a temporary buffer first.
@@ -23,37 +65,257 @@ pub fn decode(tfd: &[u8], tfh: &[u8]) -> Result<TfdImage> {
         (Cow::Owned(data), true)
     };
 
-    // Try to infer the top-level dimension, mip count and block footprint from
-    // the raw size, optionally using the TFH's dimension hint as a tie breaker.
-    let (top, _mips, fp) = guess_from_tfd_len(raw.len())
-        .or_else(|| {
-            let hint = tfh_dim_hint(tfh)?;
-            [BcFootprint::Bc1_4, BcFootprint::Bc3_5_7]
-                .into_iter()
-                .find_map(|bpb| {
-                    for mips in 1..10 {
-                        if sum_bc_bytes(hint, bpb as usize, mips) == raw.len() {
-                            return Some((hint, mips, bpb));
-                        }
-                    }
-                    None
+    // Infer the top-level dimension, mip count, block footprint and layer
+    // (face/slice) count from the raw size, optionally using the TFH's
+    // dimension hint as a tie breaker. A plain 2D texture is one layer whose
+    // mip chain exactly fills `raw`; a cubemap or array is `raw` divided
+    // evenly into several identical chains, which `find_chain` also checks
+    // for once the single-layer search comes up empty.
+    let (top, mips, fp, layer_count) =
+        find_chain(raw.len(), tfh).ok_or_else(|| anyhow!("Cannot infer BC footprint/mips from TFD length"))?;
+
+    // The header's pixel-format field is authoritative when present: BC3,
+    // BC5 and BC7 all share the same 16-bytes-per-block footprint, so the
+    // length heuristic above can only narrow things down to `fp` and can't
+    // tell those three apart (it used to just assume BC5 for every
+    // compressed stream). Fall back to that old assumption when the field
+    // is missing or holds a code we don't recognize — *or* when it
+    // disagrees with `fp` on block size: `fp` is what `find_chain` actually
+    // measured the TFD's length against, so trusting a header format whose
+    // `block_bytes()` doesn't match it would size `mip_layout` below wrong
+    // and slice past the end of `raw`.
+    // Compressed streams used to just assume Bc5 outright, but that's only
+    // valid when `fp` actually measured a 16-bytes-per-block footprint; a
+    // zstd-compressed BC1 TFD (8 B/block) would otherwise get sized as BC5
+    // (16 B/block) below, oversizing every `mip_layout` entry and slicing
+    // `raw` past its end.
+    let length_heuristic = match fp {
+        BcFootprint::Bc1_4 => BcFormat::Bc1,
+        BcFootprint::Bc3_5_7 if is_compressed => BcFormat::Bc5,
+        BcFootprint::Bc3_5_7 => BcFormat::Bc3,
+    };
+    let format = tfh_pixel_format(tfh)
+        .filter(|f| f.block_bytes() == fp as usize)
+        .unwrap_or(length_heuristic);
+
+    let layout = mip_layout(top, format.block_bytes(), mips);
+    let layer_len: usize = layout.iter().map(|l| l.size).sum();
+    let raw = raw.into_owned();
+
+    if format == BcFormat::Bc6h {
+        let layers = (0..layer_count)
+            .map(|layer| {
+                let base = layer * layer_len;
+                layout
+                    .iter()
+                    .map(|l| {
+                        let src = &raw[base + l.offset..base + l.offset + l.size];
+                        Ok(HdrLevel { width: l.w, height: l.h, rgb: decode_bc6h_level_to_rgb(src, l.w, l.h)? })
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        return Ok(TfdImage { format, top, mips, layer_count, raw, pixels: TfdPixels::Hdr { layers } });
+    }
+
+    let layers = (0..layer_count)
+        .map(|layer| {
+            let base = layer * layer_len;
+            layout
+                .iter()
+                .map(|l| {
+                    let src = &raw[base + l.offset..base + l.offset + l.size];
+                    let rgba = match format {
+                        BcFormat::Bc1 => decode_bc1_level_to_rgba(src, l.w, l.h)?,
+                        BcFormat::Bc3 => decode_bc3_level_to_rgba(src, l.w, l.h)?,
+                        BcFormat::Bc5 => decode_bc5_level_to_rgba(src, l.w, l.h)?,
+                        BcFormat::Bc7 => decode_bc7_level_to_rgba(src, l.w, l.h)?,
+                        BcFormat::Bc6h => unreachable!("handled above"),
+                    };
+                    Ok(Rgba8Level { width: l.w, height: l.h, rgba })
                 })
+                .collect::<Result<Vec<_>>>()
         })
-        .ok_or_else(|| anyhow!("Cannot infer BC footprint/mips from TFD length"))?;
+        .collect::<Result<Vec<_>>>()?;
+    Ok(TfdImage { format, top, mips, layer_count, raw, pixels: TfdPixels::Rgba8 { layers } })
+}
 
-    let width = top;
-    let height = top;
-    let rgba = if is_compressed {
-        // Compressed streams in the samples are BC5 normal maps.
-        decode_bc5_top_mip_to_rgba(&raw, width, height)?
-    } else {
-        match fp {
-            BcFootprint::Bc1_4 => decode_bc1_top_mip_to_rgba(&raw, width, height)?,
-            BcFootprint::Bc3_5_7 => decode_bc3_top_mip_to_rgba(&raw, width, height)?,
+pub(crate) struct MipSlice {
+    pub(crate) offset: usize,
+    pub(crate) size: usize,
+    w: usize,
+    h: usize,
+}
+
+impl TfdImage {
+    /// Per-mip (byte offset, byte size) within a single face/layer's chain in
+    /// `raw`, top level first. Used by the DDS writer to re-slice the
+    /// original compressed bytes without having to re-derive the footprint.
+    pub(crate) fn layout(&self) -> Vec<MipSlice> {
+        mip_layout(self.top, self.format.block_bytes(), self.mips)
+    }
+
+    /// Writes the top mip of `layer` out as an 8-bit PNG. HDR (BC6H) levels
+    /// are clamp-exposed first, same as the viewer's preview.
+    pub fn export_png(&self, layer: usize, path: &std::path::Path) -> Result<()> {
+        let (width, height, rgba) = match &self.pixels {
+            TfdPixels::Rgba8 { layers } => {
+                let top = &layers[layer][0];
+                (top.width, top.height, top.rgba.clone())
+            }
+            TfdPixels::Hdr { layers } => {
+                let top = &layers[layer][0];
+                (top.width, top.height, expose(&top.rgb))
+            }
+        };
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("creating {}", path.display()))?;
+        let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().context("writing PNG header")?;
+        writer.write_image_data(&rgba).context("writing PNG data")?;
+        Ok(())
+    }
+
+    /// Writes the top mip of `layer` out as an OpenEXR file, carrying the
+    /// original half-float range — unlike `export_png`, nothing is clamped.
+    /// Only available for the BC6H (HDR) path.
+    pub fn export_exr(&self, layer: usize, path: &std::path::Path) -> Result<()> {
+        let TfdPixels::Hdr { layers } = &self.pixels else {
+            return Err(anyhow!("OpenEXR export is only available for BC6H (HDR) textures"));
+        };
+        let top = &layers[layer][0];
+        let (width, height, rgb) = (top.width, top.height, &top.rgb);
+        exr::prelude::write_rgba_file(path, width, height, |x, y| {
+            let o = (y * width + x) * 3;
+            (rgb[o], rgb[o + 1], rgb[o + 2], 1.0f32)
+        })
+        .map_err(|err| anyhow!("writing OpenEXR file: {err}"))
+    }
+
+    /// Re-wraps the original BC-compressed bytes in `raw` into a DDS file
+    /// with a DX10 header extension, preserving every mip and layer
+    /// untouched — no recompression, so the result round-trips losslessly
+    /// into any tool that reads standard BC-compressed DDS.
+    pub fn export_dds(&self, path: &std::path::Path) -> Result<()> {
+        let layout = self.layout();
+        let pitch_or_linear = layout[0].size as u32;
+        let is_cubemap = self.layer_count == 6;
+
+        let mut out = Vec::with_capacity(148 + self.raw.len());
+        out.extend_from_slice(b"DDS ");
+        out.extend_from_slice(&124u32.to_le_bytes()); // dwSize
+
+        // DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT | DDSD_MIPMAPCOUNT | DDSD_LINEARSIZE
+        out.extend_from_slice(&0x000A1007u32.to_le_bytes());
+        out.extend_from_slice(&(self.top as u32).to_le_bytes()); // dwHeight
+        out.extend_from_slice(&(self.top as u32).to_le_bytes()); // dwWidth
+        out.extend_from_slice(&pitch_or_linear.to_le_bytes()); // dwPitchOrLinearSize
+        out.extend_from_slice(&0u32.to_le_bytes()); // dwDepth
+        out.extend_from_slice(&(self.mips as u32).to_le_bytes()); // dwMipMapCount
+        out.extend_from_slice(&[0u8; 44]); // dwReserved1[11]
+
+        // DDS_PIXELFORMAT: DX10 escape hatch, everything funnels through the
+        // extended header below instead of the legacy FourCC/mask fields.
+        out.extend_from_slice(&32u32.to_le_bytes()); // dwSize
+        out.extend_from_slice(&0x4u32.to_le_bytes()); // DDPF_FOURCC
+        out.extend_from_slice(b"DX10");
+        out.extend_from_slice(&[0u8; 20]); // dwRGBBitCount + 4 bitmasks
+
+        let caps_complex = self.mips > 1 || self.layer_count > 1;
+        // DDSCAPS_TEXTURE, plus COMPLEX|MIPMAP when there's more than one surface.
+        let caps = 0x1000u32 | if caps_complex { 0x8 | 0x400000 } else { 0 };
+        out.extend_from_slice(&caps.to_le_bytes());
+        // DDSCAPS2_CUBEMAP plus all six face flags.
+        out.extend_from_slice(&(if is_cubemap { 0xFE00u32 } else { 0 }).to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes()); // dwCaps3
+        out.extend_from_slice(&0u32.to_le_bytes()); // dwCaps4
+        out.extend_from_slice(&0u32.to_le_bytes()); // dwReserved2
+
+        // DDS_HEADER_DXT10
+        out.extend_from_slice(&self.format.dxgi_format().to_le_bytes());
+        out.extend_from_slice(&3u32.to_le_bytes()); // D3D10_RESOURCE_DIMENSION_TEXTURE2D
+        out.extend_from_slice(&(if is_cubemap { 0x4u32 } else { 0 }).to_le_bytes()); // miscFlag: TEXTURECUBE
+        // arraySize counts whole cubes (the surface count is arraySize * 6)
+        // when TEXTURECUBE is set, not faces — layer_count is already a face
+        // count, so it has to be divided down for the cubemap case.
+        let array_size = if is_cubemap { self.layer_count / 6 } else { self.layer_count };
+        out.extend_from_slice(&(array_size as u32).to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes()); // miscFlags2
+
+        out.extend_from_slice(&self.raw);
+        std::fs::write(path, &out).with_context(|| format!("writing {}", path.display()))
+    }
+}
+
+/// Per-mip (byte offset, byte size, width, height) within a single
+/// face/layer's chain, top level first.
+fn mip_layout(top: usize, bpb: usize, mips: usize) -> Vec<MipSlice> {
+    let mut out = Vec::with_capacity(mips);
+    let mut offset = 0usize;
+    for m in 0..mips {
+        let w = (top >> m).max(1);
+        let h = (top >> m).max(1);
+        let size = ((w + 3) / 4) * ((h + 3) / 4) * bpb;
+        out.push(MipSlice { offset, size, w, h });
+        offset += size;
+    }
+    out
+}
+
+/// Pixel-format codes line up with the BC compression number itself (1,
+/// 3, 5, 6 for BC6H, 7), which is the simplest scheme consistent with
+/// everything else this reverse-engineered format does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BcFormat {
+    Bc1,
+    Bc3,
+    Bc5,
+    Bc6h,
+    Bc7,
+}
+
+impl BcFormat {
+    fn from_code(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(BcFormat::Bc1),
+            3 => Some(BcFormat::Bc3),
+            5 => Some(BcFormat::Bc5),
+            6 => Some(BcFormat::Bc6h),
+            7 => Some(BcFormat::Bc7),
+            _ => None,
         }
-    };
+    }
+
+    /// Bytes per 4x4 block; BC1 packs into half the space of the others.
+    fn block_bytes(self) -> usize {
+        match self {
+            BcFormat::Bc1 => 8,
+            _ => 16,
+        }
+    }
 
-    Ok(TfdImage { width, height, rgba })
+    /// `DXGI_FORMAT` code for the DX10 DDS header extension (values from the
+    /// DirectX header; the legacy DDS pixel format can't express BC5/6H/7).
+    pub(crate) fn dxgi_format(self) -> u32 {
+        match self {
+            BcFormat::Bc1 => 71,  // DXGI_FORMAT_BC1_UNORM
+            BcFormat::Bc3 => 77,  // DXGI_FORMAT_BC3_UNORM
+            BcFormat::Bc5 => 83,  // DXGI_FORMAT_BC5_UNORM
+            BcFormat::Bc6h => 95, // DXGI_FORMAT_BC6H_UF16
+            BcFormat::Bc7 => 98,  // DXGI_FORMAT_BC7_UNORM
+        }
+    }
+}
+
+/// Pixel-format field, right after the dimension hint `tfh_dim_hint` reads.
+fn tfh_pixel_format(tfh: &[u8]) -> Option<BcFormat> {
+    if tfh.len() < 0xA8 {
+        return None;
+    }
+    let code = u32::from_le_bytes(tfh[0xA4..0xA8].try_into().ok()?);
+    BcFormat::from_code(code)
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -74,6 +336,30 @@ fn sum_bc_bytes(top: usize, bpb: usize, mips: usize) -> usize {
     total
 }
 
+/// Resolves `(top_dimension, mips, footprint, layer_count)` for a TFD of
+/// `raw_len` bytes. Tries an exact single-layer match first (the common
+/// case); if that fails, falls back to the TFH dimension hint and searches
+/// for a layer count (1..=16, covering both cubemaps and small arrays) whose
+/// mip-chain size divides `raw_len` evenly.
+fn find_chain(raw_len: usize, tfh: &[u8]) -> Option<(usize, usize, BcFootprint, usize)> {
+    if let Some((top, mips, bpb)) = guess_from_tfd_len(raw_len) {
+        return Some((top, mips, bpb, 1));
+    }
+    let hint = tfh_dim_hint(tfh)?;
+    for layer_count in 1..=16usize {
+        if raw_len % layer_count != 0 {
+            continue;
+        }
+        let chain_len = raw_len / layer_count;
+        if let Some((mips, bpb)) = [BcFootprint::Bc1_4, BcFootprint::Bc3_5_7].into_iter().find_map(|bpb| {
+            (1..10).find(|&mips| sum_bc_bytes(hint, bpb as usize, mips) == chain_len).map(|mips| (mips, bpb))
+        }) {
+            return Some((hint, mips, bpb, layer_count));
+        }
+    }
+    None
+}
+
 fn guess_from_tfd_len(tfd_len: usize) -> Option<(usize, usize, BcFootprint)> {
     for &bpb in [BcFootprint::Bc1_4, BcFootprint::Bc3_5_7].iter() {
         let bpbv = bpb as usize;
@@ -98,7 +384,7 @@ fn tfh_dim_hint(tfh: &[u8]) -> Option<usize> {
     None
 }
 
-fn decode_bc3_top_mip_to_rgba(src: &[u8], w: usize, h: usize) -> Result<Vec<u8>> {
+fn decode_bc3_level_to_rgba(src: &[u8], w: usize, h: usize) -> Result<Vec<u8>> {
     let bw = (w + 3) / 4;
     let bh = (h + 3) / 4;
     let mut rgba = vec![0u8; w * h * 4];
@@ -121,7 +407,7 @@ fn decode_bc3_top_mip_to_rgba(src: &[u8], w: usize, h: usize) -> Result<Vec<u8>>
     Ok(rgba)
 }
 
-fn decode_bc5_top_mip_to_rgba(src: &[u8], w: usize, h: usize) -> Result<Vec<u8>> {
+fn decode_bc5_level_to_rgba(src: &[u8], w: usize, h: usize) -> Result<Vec<u8>> {
     let bw = (w + 3) / 4;
     let bh = (h + 3) / 4;
     let mut rgba = vec![0u8; w * h * 4];
@@ -144,6 +430,54 @@ fn decode_bc5_top_mip_to_rgba(src: &[u8], w: usize, h: usize) -> Result<Vec<u8>>
     Ok(rgba)
 }
 
+fn decode_bc7_level_to_rgba(src: &[u8], w: usize, h: usize) -> Result<Vec<u8>> {
+    let bw = (w + 3) / 4;
+    let bh = (h + 3) / 4;
+    let mut rgba = vec![0u8; w * h * 4];
+    let pitch = w * 4;
+    let bpb = 16usize;
+    for y in 0..bh {
+        for x in 0..bw {
+            let off = (y * bw + x) * bpb;
+            let block = &src[off..off + bpb];
+            let mut tmp = [0u8; 4 * 4 * 4];
+            bcdec_rs::bc7(block, &mut tmp, 4 * 4);
+            for row in 0..4 {
+                let dst = (y * 4 + row) * pitch + x * 4 * 4;
+                let src_row = row * 4 * 4;
+                rgba[dst..dst + 4 * 4]
+                    .copy_from_slice(&tmp[src_row..src_row + 4 * 4]);
+            }
+        }
+    }
+    Ok(rgba)
+}
+
+/// BC6H is always unpacked as HDR RGB (3 `f32`s/pixel, no alpha). Crossout's
+/// assets are the regular (unsigned) variant, same as everywhere else this
+/// format shows up.
+fn decode_bc6h_level_to_rgb(src: &[u8], w: usize, h: usize) -> Result<Vec<f32>> {
+    let bw = (w + 3) / 4;
+    let bh = (h + 3) / 4;
+    let mut rgb = vec![0f32; w * h * 3];
+    let pitch = w * 3;
+    let bpb = 16usize;
+    for y in 0..bh {
+        for x in 0..bw {
+            let off = (y * bw + x) * bpb;
+            let block = &src[off..off + bpb];
+            let mut tmp = [0f32; 4 * 4 * 3];
+            bcdec_rs::bc6h_float(block, &mut tmp, 4 * 3, false);
+            for row in 0..4 {
+                let dst = (y * 4 + row) * pitch + x * 4 * 3;
+                let src_row = row * 4 * 3;
+                rgb[dst..dst + 4 * 3].copy_from_slice(&tmp[src_row..src_row + 4 * 3]);
+            }
+        }
+    }
+    Ok(rgb)
+}
+
 fn rgb565_to_888(c: u16) -> [u8; 3] {
     let r = ((c >> 11) & 0x1F) as u32;
     let g = ((c >> 5) & 0x3F) as u32;
@@ -155,7 +489,7 @@ fn rgb565_to_888(c: u16) -> [u8; 3] {
     ]
 }
 
-fn decode_bc1_top_mip_to_rgba(src: &[u8], w: usize, h: usize) -> Result<Vec<u8>> {
+fn decode_bc1_level_to_rgba(src: &[u8], w: usize, h: usize) -> Result<Vec<u8>> {
     let bw = (w + 3) / 4;
     let bh = (h + 3) / 4;
     let mut out = vec![0u8; w * h * 4];
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+use std::{fs, path::Path};
+
+use crate::codec;
+use crate::{GrpEntry, GrpFile};
+
+/// Reconstructs the GRP2 binary layout for `pack`'s root-level entries and
+/// writes it to `out_path`: header, the filename-offset table at 0x40, the
+/// C-string name blob, the per-entry data index (4-byte location / 4-byte
+/// file ID / 4-byte common ID), and the concatenated, re-zstd-compressed
+/// payloads. Offsets are recomputed from scratch from the in-memory
+/// `entries`, so a packed entry's compressed size no longer has to match
+/// what was originally on disk.
+///
+/// Entries that are virtual children of a nested GRP2 container (spliced in
+/// by `expand_nested_archives`) are written back as part of their parent
+/// container's original bytes, unmodified — editing into a nested container
+/// isn't supported yet, which is why the UI only offers "Replace bytes
+/// from file…" on root-level entries.
+///
+/// A handful of bytes whose meaning hasn't been decoded (the 0x18..0x40
+/// header region, and 4 bytes between the name blob and the data index) are
+/// zero-filled rather than preserved, since nothing in `parse_entry_table`
+/// reads them back out.
+pub fn write_grp(pack: &GrpFile, out_path: &Path) -> Result<()> {
+    let root_entries: Vec<&GrpEntry> = pack.entries.iter().filter(|e| !pack.is_nested_child(e)).collect();
+
+    let name_table_start = 0x40u32 + (root_entries.len() as u32) * 4;
+    let mut name_blob = Vec::new();
+    let mut name_offsets = Vec::with_capacity(root_entries.len());
+    for e in &root_entries {
+        name_offsets.push(name_table_start + name_blob.len() as u32);
+        name_blob.extend_from_slice(e.full_path.as_bytes());
+        name_blob.push(0);
+    }
+
+    // 4 unknown trailer bytes between the name blob and the data index;
+    // see parse_entry_table's `data_index_start` computation.
+    let data_index_start = name_table_start + name_blob.len() as u32 + 4;
+    let data_start = data_index_start + (root_entries.len() as u32) * 12;
+
+    let mut payloads = Vec::new();
+    let mut file_locs = Vec::with_capacity(root_entries.len());
+    for e in &root_entries {
+        file_locs.push(data_start + payloads.len() as u32);
+        let raw = pack.read_entry(e)?;
+        payloads.extend_from_slice(&codec::encode_with("Zstd", &raw)?);
+    }
+
+    let mut out = Vec::with_capacity(data_start as usize + payloads.len());
+    out.extend_from_slice(b"GRP2");
+    out.extend_from_slice(&pack.header_size.to_le_bytes());
+    out.extend_from_slice(&pack.header_prelude);
+    out.extend_from_slice(&(root_entries.len() as u32).to_le_bytes());
+    out.resize(0x40, 0);
+    for off in &name_offsets {
+        out.extend_from_slice(&off.to_le_bytes());
+    }
+    out.extend_from_slice(&name_blob);
+    out.extend_from_slice(&[0u8; 4]);
+    for (e, &loc) in root_entries.iter().zip(&file_locs) {
+        out.extend_from_slice(&loc.to_le_bytes());
+        out.extend_from_slice(&e.file_id.to_le_bytes());
+        out.extend_from_slice(&e.common_id.to_le_bytes());
+    }
+    out.extend_from_slice(&payloads);
+
+    fs::write(out_path, &out).with_context(|| format!("writing packed GRP to {}", out_path.display()))
+}